@@ -0,0 +1,53 @@
+use std::io::{self, Read};
+
+const AR_MAGIC: &[u8; 8] = b"!<arch>\n";
+
+/// Size of an ar member header
+const HEADER_LEN: usize = 60;
+
+/// Finds the first member of an ar archive (as used by .deb packages) whose name starts with
+/// prefix, returning a reader for its contents
+pub fn ar_member<R: Read>(mut reader: R, prefix: &str) -> io::Result<Option<io::Take<R>>> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+
+    if &magic != AR_MAGIC {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not an ar archive",
+        ))?
+    }
+
+    loop {
+        let mut header = [0u8; HEADER_LEN];
+
+        match reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => Err(e)?,
+        }
+
+        // Name is the first 16 bytes (GNU ar terminates it with '/'), size is bytes 48 to 58
+        let name = String::from_utf8_lossy(&header[..16]);
+        let name = name.trim_end().trim_end_matches('/');
+
+        let size = std::str::from_utf8(&header[48..58])
+            .ok()
+            .and_then(|size| size.trim().parse::<u64>().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid ar member size"))?;
+
+        if name.starts_with(prefix) {
+            return Ok(Some(reader.take(size)));
+        }
+
+        // Skip the member, which is padded to an even length
+        let skip = size + (size % 2);
+
+        if io::copy(&mut reader.by_ref().take(skip), &mut io::sink())? != skip {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Truncated ar archive",
+            ))?
+        }
+    }
+}
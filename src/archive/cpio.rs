@@ -0,0 +1,99 @@
+use std::io::{self, Read};
+
+/// Magic numbers of the "new" ASCII cpio format without and with checksums
+const NEWC_MAGIC: &[u8; 6] = b"070701";
+const CRC_MAGIC: &[u8; 6] = b"070702";
+
+/// Size of a newc header
+const HEADER_LEN: usize = 110;
+
+const TRAILER: &str = "TRAILER!!!";
+
+/// An entry in a cpio archive
+pub struct CpioEntry {
+    pub name: String,
+    pub mode: u32,
+    pub data: Vec<u8>,
+}
+
+/// Finds an entry in a newc format cpio stream (as used by rpm payloads) for which matches
+/// returns true. Hard linked files share an inode number and only the last of the set carries
+/// the contents, so for a hard link without contents the rest of the stream is searched
+pub fn cpio_find(
+    mut reader: impl Read,
+    matches: impl Fn(&str) -> bool,
+) -> io::Result<Option<CpioEntry>> {
+    // Matching hard link without contents, and its inode number
+    let mut linked: Option<(CpioEntry, u32)> = None;
+
+    loop {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        if &header[..6] != NEWC_MAGIC && &header[..6] != CRC_MAGIC {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unsupported cpio format",
+            ))?
+        }
+
+        // Fields are 8 hex digits following the magic
+        let field = |i: usize| -> io::Result<u32> {
+            let start = 6 + i * 8;
+
+            std::str::from_utf8(&header[start..start + 8])
+                .ok()
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid cpio header"))
+        };
+
+        let ino = field(0)?;
+        let mode = field(1)?;
+        let nlink = field(4)?;
+        let size = field(6)? as usize;
+        let name_size = field(11)? as usize;
+
+        // Name is NUL terminated, and header plus name is padded to a multiple of 4
+        let mut name = vec![0u8; name_size + pad4(HEADER_LEN + name_size)];
+        reader.read_exact(&mut name)?;
+
+        let name = String::from_utf8_lossy(&name[..name_size.saturating_sub(1)]).into_owned();
+
+        if name == TRAILER {
+            return match linked {
+                Some((entry, _)) => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("No contents found for hard link {}", entry.name),
+                )),
+                None => Ok(None),
+            };
+        }
+
+        // Data is padded to a multiple of 4
+        let mut data = vec![0u8; size + pad4(size)];
+        reader.read_exact(&mut data)?;
+
+        data.truncate(size);
+
+        match linked.take() {
+            Some((entry, linked_ino)) if linked_ino == ino && size > 0 => {
+                return Ok(Some(CpioEntry { data, ..entry }));
+            }
+            Some(still_linked) => linked = Some(still_linked),
+            None if matches(&name) => {
+                let entry = CpioEntry { name, mode, data };
+
+                if mode & libc::S_IFMT == libc::S_IFREG && nlink > 1 && size == 0 {
+                    linked = Some((entry, ino));
+                } else {
+                    return Ok(Some(entry));
+                }
+            }
+            None => (),
+        }
+    }
+}
+
+fn pad4(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
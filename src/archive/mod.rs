@@ -0,0 +1,167 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use tar::{Archive, EntryType};
+use xz2::read::XzDecoder;
+
+pub use ar::ar_member;
+use cpio::cpio_find;
+use rpm::rpm_payload;
+
+mod ar;
+mod cpio;
+mod rpm;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Limit on following tar hard links, in case of a loop
+const MAX_HARD_LINKS: usize = 8;
+
+/// Wraps a reader with a decompressor chosen by the magic number at the start of the stream.
+/// Uncompressed streams are passed through unchanged
+pub fn decompress<'a>(reader: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+
+    let magic = reader.fill_buf()?;
+
+    let reader: Box<dyn Read> = if magic.starts_with(GZIP_MAGIC) {
+        Box::new(MultiGzDecoder::new(reader))
+    } else if magic.starts_with(XZ_MAGIC) {
+        Box::new(XzDecoder::new_multi_decoder(reader))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        Box::new(
+            StreamingDecoder::new(reader)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        )
+    } else {
+        Box::new(reader)
+    };
+
+    Ok(reader)
+}
+
+/// A file extracted from a package archive
+pub struct Member {
+    /// File type and permission bits
+    pub mode: u32,
+    /// Contents of a regular file
+    pub data: Vec<u8>,
+    /// Target of a symbolic link
+    pub link: Option<PathBuf>,
+}
+
+/// A matching tar entry
+enum TarEntry {
+    Member(Member),
+    /// A hard link to an earlier entry installed at the path
+    HardLink(PathBuf),
+}
+
+/// Extracts the file installed at path from a package archive (.deb, .rpm or a possibly
+/// compressed tar archive such as .xbps)
+pub fn extract_member(archive: &Path, path: &Path) -> Result<Option<Member>, Box<dyn Error>> {
+    let mut path = path.to_path_buf();
+
+    // Tar hard links refer back to an earlier entry holding the contents, so the archive is
+    // read again to find it
+    for _ in 0..MAX_HARD_LINKS {
+        match find_member(archive, &path)? {
+            Some(TarEntry::Member(member)) => return Ok(Some(member)),
+            Some(TarEntry::HardLink(target)) => path = target,
+            None => return Ok(None),
+        }
+    }
+
+    Err(format!("Too many hard links to {}", path.display()))?
+}
+
+/// Finds the file installed at path in a package archive
+fn find_member(archive: &Path, path: &Path) -> Result<Option<TarEntry>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(archive)?);
+
+    let extension = archive.extension().and_then(|ext| ext.to_str());
+
+    match extension {
+        Some("deb") => match ar_member(reader, "data.tar")? {
+            Some(data) => tar_member(decompress(data)?, path),
+            None => Err("No data.tar member found in .deb")?,
+        },
+        Some("rpm") => {
+            let payload = decompress(rpm_payload(reader)?)?;
+
+            Ok(
+                cpio_find(payload, |name| archive_path(Path::new(name)) == path)?.map(|entry| {
+                    let link = (entry.mode & libc::S_IFMT == libc::S_IFLNK)
+                        .then(|| PathBuf::from(String::from_utf8_lossy(&entry.data).as_ref()));
+
+                    TarEntry::Member(Member {
+                        mode: entry.mode,
+                        data: entry.data,
+                        link,
+                    })
+                }),
+            )
+        }
+        _ => tar_member(decompress(reader)?, path),
+    }
+}
+
+/// Finds and reads the file installed at path from a tar stream
+fn tar_member(reader: impl Read, path: &Path) -> Result<Option<TarEntry>, Box<dyn Error>> {
+    let mut archive = Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if archive_path(&entry.path()?) != path {
+            continue;
+        }
+
+        if entry.header().entry_type() == EntryType::Link {
+            return match entry.link_name()? {
+                Some(target) => Ok(Some(TarEntry::HardLink(archive_path(&target)))),
+                None => Err(format!("Hard link {} has no target", path.display()))?,
+            };
+        }
+
+        let file_type = match entry.header().entry_type() {
+            EntryType::Directory => libc::S_IFDIR,
+            EntryType::Symlink => libc::S_IFLNK,
+            EntryType::Char => libc::S_IFCHR,
+            EntryType::Block => libc::S_IFBLK,
+            EntryType::Fifo => libc::S_IFIFO,
+            _ => libc::S_IFREG,
+        };
+
+        let mode = file_type | (entry.header().mode()? & 0o7777);
+
+        let link = entry.link_name()?.map(|link| link.into_owned());
+
+        let mut data = Vec::new();
+
+        if file_type == libc::S_IFREG {
+            entry.read_to_end(&mut data)?;
+        }
+
+        return Ok(Some(TarEntry::Member(Member { mode, data, link })));
+    }
+
+    Ok(None)
+}
+
+/// Converts an archive member name (eg. ./usr/bin/ls) to an absolute installed path
+fn archive_path(name: &Path) -> PathBuf {
+    Path::new("/").join(
+        name.components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect::<PathBuf>(),
+    )
+}
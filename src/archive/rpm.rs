@@ -0,0 +1,64 @@
+use std::io::{self, Read};
+
+const LEAD_MAGIC: &[u8; 4] = &[0xed, 0xab, 0xee, 0xdb];
+const HEADER_MAGIC: &[u8; 3] = &[0x8e, 0xad, 0xe8];
+
+/// Size of the rpm lead
+const LEAD_LEN: usize = 96;
+
+/// Skips the lead, signature and header of an rpm package, leaving the reader at the start of the
+/// (compressed) cpio payload
+pub fn rpm_payload<R: Read>(mut reader: R) -> io::Result<R> {
+    let mut lead = [0u8; LEAD_LEN];
+    reader.read_exact(&mut lead)?;
+
+    if &lead[..4] != LEAD_MAGIC {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not an rpm package",
+        ))?
+    }
+
+    // The signature header is padded to a multiple of 8 bytes
+    let sig_len = skip_header(&mut reader)?;
+    skip(&mut reader, (8 - sig_len % 8) % 8)?;
+
+    // Main header
+    skip_header(&mut reader)?;
+
+    Ok(reader)
+}
+
+/// Skips an rpm header structure, returning its length
+fn skip_header(reader: &mut impl Read) -> io::Result<u64> {
+    let mut intro = [0u8; 16];
+    reader.read_exact(&mut intro)?;
+
+    if &intro[..3] != HEADER_MAGIC {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid rpm header",
+        ))?
+    }
+
+    // Index entry count and data size follow the magic, version and reserved bytes
+    let entries = u32::from_be_bytes(intro[8..12].try_into().unwrap()) as u64;
+    let data_len = u32::from_be_bytes(intro[12..16].try_into().unwrap()) as u64;
+
+    let len = entries * 16 + data_len;
+
+    skip(reader, len)?;
+
+    Ok(16 + len)
+}
+
+fn skip(reader: &mut impl Read, len: u64) -> io::Result<()> {
+    if io::copy(&mut reader.take(len), &mut io::sink())? != len {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Truncated rpm package",
+        ))?
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::{
+    os::linux::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use crate::{owns::ownership, packageman::PackageDb};
+
+use super::{
+    compile_ignores,
+    new::{linked_to_owned, should_recurse, skipped_filesystem},
+    report::Report,
+    verify::verify_files,
+    CheckArgs,
+};
+
+/// Runs the check decision chain for a single absolute path and prints the outcome
+pub fn explain(packagedb: &PackageDb, args: CheckArgs, path: &Path) {
+    let mut report = Report::new(compile_ignores(packagedb, &args.ignores));
+
+    println!("{}", path.display());
+    println!("  ownership: {}", ownership(packagedb, path));
+
+    // Verify packaged files at the path
+    if args.changed || args.missing {
+        let files = packagedb
+            .find_files(path)
+            .into_iter()
+            .filter(|file| args.packages.selects(file))
+            .collect::<Vec<_>>();
+
+        if files.is_empty() {
+            println!("  verify:    no packaged files to verify");
+        } else {
+            verify_files(packagedb, &args, &mut report, files.into_iter());
+
+            match report.items().count() {
+                0 => println!("  verify:    no differences found"),
+                count => println!("  verify:    {count} differences found"),
+            }
+        }
+    } else {
+        println!("  verify:    disabled");
+    }
+
+    // Follow the new file walk down to the path
+    if args.new {
+        let (desc, new) = explain_new(packagedb, path);
+
+        println!("  new scan:  {desc}");
+
+        if let Some(new) = new {
+            let mode = new
+                .symlink_metadata()
+                .map(|meta| meta.st_mode())
+                .unwrap_or(0);
+            report.add_new(new, mode);
+        }
+    } else {
+        println!("  new scan:  disabled");
+    }
+
+    // Show each report item and whether it is filtered
+    let mut reported = false;
+
+    for item in report.items() {
+        match report.find_ignore(item.path()) {
+            Some(ignore) => println!(
+                "  filtered:  {item} by {} rule {}",
+                ignore.source(),
+                ignore.as_str()
+            ),
+            None => {
+                println!("  report:    {item}");
+                reported = true;
+            }
+        }
+    }
+
+    println!(
+        "  verdict:   {}",
+        if reported { "reported" } else { "not reported" }
+    );
+}
+
+/// Walks from the root towards the path as check_new would, describing where the walk ends and
+/// returning the path which is reported as new, if any
+fn explain_new(packagedb: &PackageDb, path: &Path) -> (String, Option<PathBuf>) {
+    let mut ents = path.ancestors().collect::<Vec<_>>();
+    ents.reverse();
+
+    for ent in ents.into_iter().skip(1) {
+        let cpath = packagedb.canonical(ent);
+        let last = ent == path;
+
+        if packagedb.find_canonical(&cpath) {
+            if last {
+                return (String::from("owned, not new"), None);
+            }
+
+            if packagedb.find_canonical_tree(&cpath) {
+                return (
+                    format!(
+                        "inside {}, which is owned in its entirety and not walked",
+                        ent.display()
+                    ),
+                    None,
+                );
+            }
+
+            if !should_recurse(ent) {
+                let reason = if ent.is_symlink() {
+                    String::from("is a symbolic link, which is not followed")
+                } else if !ent.is_dir() {
+                    String::from("is not a directory")
+                } else {
+                    match skipped_filesystem(ent) {
+                        Some(fstype) => format!("is on a {fstype} filesystem, which is not walked"),
+                        None => String::from("is not walked"),
+                    }
+                };
+
+                return (format!("{} {reason}", ent.display()), None);
+            }
+        } else if linked_to_owned(packagedb, ent) {
+            return (
+                format!(
+                    "{} is a symbolic link to a packaged file, not new",
+                    ent.display()
+                ),
+                None,
+            );
+        } else if ent.symlink_metadata().is_err() {
+            return (format!("{} does not exist", ent.display()), None);
+        } else if last {
+            return (String::from("not owned, new"), Some(ent.to_path_buf()));
+        } else {
+            return (
+                format!(
+                    "inside new directory {}, which is reported instead",
+                    ent.display()
+                ),
+                Some(ent.to_path_buf()),
+            );
+        }
+    }
+
+    // The root directory is always walked
+    (String::from("root directory, walked"), None)
+}
@@ -0,0 +1,79 @@
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::packageman::{glob_to_regex, Package, PackageDb, PackageFile};
+
+/// Selects packages to check by name glob
+#[derive(Default)]
+pub struct PackageFilter {
+    /// Indexes of the selected packages, or None if no packages are filtered
+    selected: Option<HashSet<usize>>,
+    /// Whether files without a package are selected
+    unpackaged: bool,
+}
+
+impl PackageFilter {
+    /// Matches the globs against each package in the database
+    pub fn new(
+        packagedb: &PackageDb,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, regex::Error> {
+        if include.is_empty() && exclude.is_empty() {
+            return Ok(Self {
+                selected: None,
+                unpackaged: true,
+            });
+        }
+
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| Regex::new(&glob_to_regex(glob)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let include = compile(include)?;
+        let exclude = compile(exclude)?;
+
+        let selected = packagedb
+            .packages()
+            .enumerate()
+            .filter(|(_, package)| matches(package, &include, &exclude))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        Ok(Self {
+            selected: Some(selected),
+            unpackaged: include.is_empty(),
+        })
+    }
+
+    /// Returns true if the file belongs to a selected package. Files without a package are only
+    /// selected when no packages are explicitly included
+    pub fn selects(&self, file: &PackageFile) -> bool {
+        match (&self.selected, file.package()) {
+            (None, _) => true,
+            (Some(selected), Some(package)) => selected.contains(package),
+            (Some(_), None) => self.unpackaged,
+        }
+    }
+}
+
+/// Returns true if the package's name, name:arch or full name matches an include glob (or there
+/// are none), and none match an exclude glob
+fn matches(package: &Package, include: &[Regex], exclude: &[Regex]) -> bool {
+    let names = [
+        package.namestr().into_owned(),
+        package.name_arch(),
+        package.fullnamestr().into_owned(),
+    ];
+
+    let any_match = |regexes: &[Regex]| {
+        regexes
+            .iter()
+            .any(|regex| names.iter().any(|name| regex.is_match(name)))
+    };
+
+    (include.is_empty() || any_match(include)) && !any_match(exclude)
+}
@@ -0,0 +1,81 @@
+use regex::Regex;
+use std::path::Path;
+
+use crate::packageman::{Origin, PackageDb};
+
+const GLOBAL_IGNORES: [&str; 6] = [
+    "^/etc/pki/ca-trust/extracted($|/.*)",
+    "^/tmp($|/.*)",
+    "^/var/log($|/.*)",
+    "^/var/cache($|/.*)",
+    "^/var/tmp($|/.*)",
+    ".*?/__pycache__($|/.*)",
+];
+
+/// Where an ignore rule was defined
+#[derive(Clone, Copy, Debug)]
+pub enum IgnoreSource {
+    /// Built in to installdiff
+    Global,
+    /// Default for a package manager or ownership source
+    Default(Origin),
+    /// Given on the command line
+    User,
+}
+
+impl std::fmt::Display for IgnoreSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IgnoreSource::Global => f.write_str("global default"),
+            IgnoreSource::Default(origin) => f.write_fmt(format_args!("{origin} default")),
+            IgnoreSource::User => f.write_str("user"),
+        }
+    }
+}
+
+/// A compiled ignore regular expression
+pub struct Ignore {
+    regex: Regex,
+    source: IgnoreSource,
+}
+
+impl Ignore {
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.regex.is_match(&path.to_string_lossy())
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn source(&self) -> IgnoreSource {
+        self.source
+    }
+}
+
+/// Compiles the global, package manager and user ignore regular expressions
+pub fn compile_ignores(packagedb: &PackageDb, user_ignores: &[String]) -> Vec<Ignore> {
+    let mut ignores = Vec::new();
+
+    let mut add_ignore = |ignore: &str, source| {
+        if let Ok(regex) = Regex::new(ignore) {
+            ignores.push(Ignore { regex, source });
+        } else {
+            eprintln!("ERROR: Failed to compile regex '{}'", ignore);
+        }
+    };
+
+    for ignore in GLOBAL_IGNORES {
+        add_ignore(ignore, IgnoreSource::Global);
+    }
+
+    for (origin, ignore) in packagedb.ignores() {
+        add_ignore(ignore, IgnoreSource::Default(*origin));
+    }
+
+    for ignore in user_ignores {
+        add_ignore(ignore, IgnoreSource::User);
+    }
+
+    ignores
+}
@@ -1,71 +1,112 @@
 use crate::packageman::PackageDb;
+use clap::ValueEnum;
+pub use explain::explain;
+pub use filter::PackageFilter;
+pub use ignore::compile_ignores;
+use moved::find_moves;
 use new::check_new;
-use regex::Regex;
-use report::Report;
+pub use report::Report;
+use std::path::{Path, PathBuf};
 use verify::verify;
 
+mod explain;
+mod filter;
+mod ignore;
+mod moved;
+mod nar;
 mod new;
 mod report;
 mod verify;
 
+/// How file modification times are compared with the packaged times
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MtimeCheck {
+    /// Don't compare modification times
+    Off,
+    /// Report files modified after the packaged time, or after the package was installed if the
+    /// package manager doesn't record per-file times
+    #[default]
+    Newer,
+    /// Report any difference from the packaged time. Files without a per-file time aren't checked
+    Any,
+}
+
 pub struct CheckArgs {
     pub changed: bool,
     pub missing: bool,
     pub new: bool,
     pub checksum: bool,
+    pub nar: bool,
+    pub mtime: MtimeCheck,
+    /// Modification time tolerance in seconds, or None for the default
+    pub mtime_tolerance: Option<i64>,
+    pub packages: PackageFilter,
+    pub package_dirs: bool,
+    /// Subtrees to check, with directory aliases resolved
+    pub paths: Vec<PathBuf>,
+    pub summary: bool,
     pub ignores: Vec<String>,
     pub debug: u8,
 }
 
-const GLOBAL_IGNORES: [&str; 6] = [
-    "^/etc/pki/ca-trust/extracted($|/.*)",
-    "^/tmp($|/.*)",
-    "^/var/log($|/.*)",
-    "^/var/cache($|/.*)",
-    "^/var/tmp($|/.*)",
-    ".*?/__pycache__($|/.*)",
-];
-
-pub fn check(packagedb: &PackageDb, args: CheckArgs) {
-    // Compile ignores
-    let mut ignores = Vec::new();
-
-    let mut add_ignore = |ignore| {
-        if let Ok(regex) = Regex::new(ignore) {
-            ignores.push(regex);
-        } else {
-            eprintln!("ERROR: Failed to compile regex '{}'", ignore);
+impl CheckArgs {
+    /// Returns true if the path is inside one of the selected subtrees, comparing with
+    /// directory aliases resolved
+    pub fn in_scope(&self, packagedb: &PackageDb, path: &Path) -> bool {
+        if self.paths.is_empty() {
+            return true;
         }
-    };
 
-    for ignore in GLOBAL_IGNORES {
-        add_ignore(ignore);
-    }
+        let cpath = packagedb.canonical(path);
 
-    for ignore in packagedb.ignores() {
-        add_ignore(ignore);
+        self.paths.iter().any(|scope| cpath.starts_with(scope))
     }
+}
+
+pub fn check(packagedb: &PackageDb, args: CheckArgs) {
+    let report = run_check(packagedb, &args);
 
-    for ignore in &args.ignores {
-        add_ignore(ignore);
+    // Print the report
+    if args.summary {
+        report.print_summary(packagedb, args.debug);
+    } else {
+        report.print(args.debug);
     }
+}
+
+/// Runs the checks, returning the sorted report
+pub fn run_check(packagedb: &PackageDb, args: &CheckArgs) -> Report {
+    // Compile ignores
+    let ignores = compile_ignores(packagedb, &args.ignores);
 
     // Create report
     let mut report = Report::new(ignores);
 
     // Verify package files
     if args.changed || args.missing {
-        verify(packagedb, &args, &mut report);
+        verify(packagedb, args, &mut report);
+
+        // Report files packaged under two names via a directory alias (eg. /bin and /usr/bin)
+        for (aliased, other) in packagedb.aliased_duplicates().filter(|(aliased, other)| {
+            (args.packages.selects(aliased) || args.packages.selects(other))
+                && args.in_scope(packagedb, aliased.path())
+        }) {
+            report.add_aliased(packagedb, aliased, other);
+        }
     }
 
     // Check for new files
     if args.new {
-        check_new(packagedb, &mut report);
+        check_new(packagedb, args, &mut report);
+    }
+
+    // Pair missing files with new files with the same contents
+    if args.missing && args.new {
+        find_moves(&mut report, args.debug);
     }
 
     // Sort report in to file order
     report.sort();
 
-    // Print the report
-    report.print(args.debug);
+    report
 }
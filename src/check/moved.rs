@@ -0,0 +1,169 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, symlink_metadata},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    new::should_recurse,
+    report::{Report, ReportItem},
+    verify::file_digest,
+};
+
+/// A new regular file which may be a moved packaged file
+struct Candidate {
+    /// Index of the new report item for the file, or None if it is inside a new directory
+    item: Option<usize>,
+    path: PathBuf,
+    size: u64,
+}
+
+/// Pairs missing files with new regular files with the same packaged size and checksum, and
+/// replaces them with moved items. New directories are searched for candidates, as only the
+/// directory itself is reported. Missing files without a packaged size, and empty files, are
+/// never paired, so only new files of a packaged size are hashed. Without a packaged checksum a
+/// new file is only paired if it is the only one of the packaged size
+pub fn find_moves(report: &mut Report, debug: u8) {
+    // Find missing files with a packaged size
+    let missing = report
+        .items()
+        .enumerate()
+        .filter(|(_, item)| report.find_ignore(item.path()).is_none())
+        .filter_map(|(i, item)| match item {
+            // Any empty file would match an empty file
+            ReportItem::Missing(missing) => missing
+                .size()
+                .filter(|size| *size > 0)
+                .map(|size| (i, missing, size as u64)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        return;
+    }
+
+    let sizes = missing
+        .iter()
+        .map(|(_, _, size)| *size)
+        .collect::<HashSet<_>>();
+
+    // Find new regular files of a packaged size, including those inside new directories
+    let mut candidates = Vec::new();
+
+    for (i, item) in report.items().enumerate() {
+        let ReportItem::New(new) = item else {
+            continue;
+        };
+
+        if report.find_ignore(new.path()).is_some() {
+            continue;
+        }
+
+        match new.mode() & libc::S_IFMT {
+            libc::S_IFREG => add_candidate(Some(i), new.path(), &sizes, &mut candidates),
+            libc::S_IFDIR => find_candidates(report, new.path(), &sizes, &mut candidates),
+            _ => (),
+        }
+    }
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    // Candidate indexes by size
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+
+    for (j, candidate) in candidates.iter().enumerate() {
+        by_size.entry(candidate.size).or_default().push(j);
+    }
+
+    // Digests of candidates by candidate index and digest length
+    let mut digests: HashMap<(usize, usize), Option<Vec<u8>>> = HashMap::new();
+
+    let mut used = HashSet::new();
+    let mut moves = Vec::new();
+
+    for (i, missing, packaged_size) in missing {
+        let matching = by_size
+            .get(&packaged_size)
+            .into_iter()
+            .flatten()
+            .filter(|j| !used.contains(*j))
+            .map(|j| (*j, &candidates[*j]))
+            .collect::<Vec<_>>();
+
+        let found = match missing.chksum() {
+            Some(chksum) => matching.into_iter().find(|(j, candidate)| {
+                digests
+                    .entry((*j, chksum.len()))
+                    .or_insert_with(|| file_digest(&candidate.path, chksum.len()).ok())
+                    .as_deref()
+                    == Some(chksum)
+            }),
+            None => match matching.as_slice() {
+                [candidate] => Some(*candidate),
+                _ => None,
+            },
+        };
+
+        if let Some((j, candidate)) = found {
+            if debug > 1 {
+                eprintln!(
+                    "{} moved to {}",
+                    missing.path().display(),
+                    candidate.path.display()
+                );
+            }
+
+            used.insert(j);
+            moves.push((i, candidate.item, candidate.path.clone()));
+        }
+    }
+
+    report.replace_moves(moves);
+}
+
+/// Adds a new regular file as a candidate if it has one of the packaged sizes
+fn add_candidate(
+    item: Option<usize>,
+    path: &Path,
+    sizes: &HashSet<u64>,
+    candidates: &mut Vec<Candidate>,
+) {
+    if let Ok(meta) = symlink_metadata(path) {
+        if meta.is_file() && sizes.contains(&meta.len()) {
+            candidates.push(Candidate {
+                item,
+                path: path.to_path_buf(),
+                size: meta.len(),
+            });
+        }
+    }
+}
+
+/// Searches a new directory for candidates, skipping ignored paths
+fn find_candidates(
+    report: &Report,
+    dir: &Path,
+    sizes: &HashSet<u64>,
+    candidates: &mut Vec<Candidate>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for ent in entries.filter_map(|ent| ent.ok()) {
+        let path = ent.path();
+
+        if report.find_ignore(&path).is_some() {
+            continue;
+        }
+
+        if should_recurse(&path) {
+            find_candidates(report, &path, sizes, candidates);
+        } else {
+            add_candidate(None, &path, sizes, candidates);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+
+/// Hashes data written to it, counting the bytes
+struct NarHasher {
+    hasher: Sha256,
+    size: u64,
+}
+
+impl Write for NarHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl NarHasher {
+    /// Writes a NAR string (little endian u64 length, bytes, zero padding to 8 bytes)
+    fn string(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        self.write_all(bytes)?;
+        self.padding(bytes.len() as u64)
+    }
+
+    fn padding(&mut self, len: u64) -> io::Result<()> {
+        let pad = (8 - (len % 8) as usize) % 8;
+        self.write_all(&[0u8; 8][..pad])
+    }
+}
+
+/// Calculates the size and SHA256 of the NAR (Nix archive) serialisation of a path
+pub fn nar_digest(path: &Path) -> io::Result<(u64, Vec<u8>)> {
+    let mut nar = NarHasher {
+        hasher: Sha256::new(),
+        size: 0,
+    };
+
+    nar.string(b"nix-archive-1")?;
+    serialise(&mut nar, path)?;
+
+    Ok((nar.size, nar.hasher.finalize().to_vec()))
+}
+
+fn serialise(nar: &mut NarHasher, path: &Path) -> io::Result<()> {
+    let meta = path.symlink_metadata()?;
+
+    nar.string(b"(")?;
+    nar.string(b"type")?;
+
+    if meta.is_symlink() {
+        nar.string(b"symlink")?;
+        nar.string(b"target")?;
+        nar.string(fs::read_link(path)?.as_os_str().as_bytes())?;
+    } else if meta.is_dir() {
+        nar.string(b"directory")?;
+
+        let mut names = fs::read_dir(path)?
+            .map(|ent| ent.map(|ent| ent.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        names.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        for name in names {
+            nar.string(b"entry")?;
+            nar.string(b"(")?;
+            nar.string(b"name")?;
+            nar.string(name.as_bytes())?;
+            nar.string(b"node")?;
+            serialise(nar, &path.join(&name))?;
+            nar.string(b")")?;
+        }
+    } else if meta.is_file() {
+        nar.string(b"regular")?;
+
+        if meta.permissions().mode() & 0o111 != 0 {
+            nar.string(b"executable")?;
+            nar.string(b"")?;
+        }
+
+        nar.string(b"contents")?;
+
+        // Stream the contents rather than reading the file in to memory
+        let len = meta.len();
+        nar.write_all(&len.to_le_bytes())?;
+        io::copy(&mut File::open(path)?, nar)?;
+        nar.padding(len)?;
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported file type for {}", path.display()),
+        ))?
+    }
+
+    nar.string(b")")
+}
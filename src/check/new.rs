@@ -10,16 +10,54 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::packageman::PackageDb;
+use crate::packageman::{PackageDb, IMAGE_MOUNT_DIRS};
 
-use super::report::Report;
+use super::{report::Report, CheckArgs};
 
-pub fn check_new(packagedb: &PackageDb, reports: &mut Report) {
-    // Walk filesystem looking for new files
-    check_new_dir(PathBuf::from("/"), packagedb, reports);
+/// Filesystem magic number for squashfs (read-only images such as snaps, or a live system root)
+const SQUASHFS_MAGIC: libc::c_long = 0x73717368;
+
+pub fn check_new(packagedb: &PackageDb, args: &CheckArgs, reports: &mut Report) {
+    if args.package_dirs {
+        // Only look in directories owned by the selected packages
+        check_new_package_dirs(packagedb, args, reports);
+    } else if args.paths.is_empty() {
+        // Walk filesystem looking for new files
+        check_new_dir(PathBuf::from("/"), packagedb, reports, true);
+    } else {
+        // Walk each of the selected subtrees
+        for path in args.paths.iter().cloned() {
+            if path.parent().is_none() {
+                check_new_dir(path, packagedb, reports, true);
+            } else if path.symlink_metadata().is_ok() {
+                check_new_ent(path, packagedb, reports, true);
+            } else {
+                eprintln!("ERROR: Path {} does not exist", path.display());
+            }
+        }
+    }
+}
+
+/// Looks for new files only in the directories owned by the selected packages
+fn check_new_package_dirs(packagedb: &PackageDb, args: &CheckArgs, reports: &mut Report) {
+    let mut dirs = packagedb
+        .files()
+        .filter(|file| file.package().is_some() && args.packages.selects(file))
+        .filter(|file| args.in_scope(packagedb, file.path()))
+        .filter(|file| !file.is_tree() && should_recurse(file.path()))
+        .map(|file| file.path().to_path_buf())
+        .collect::<Vec<_>>();
+
+    dirs.sort();
+    dirs.dedup();
+
+    // Each owned directory is checked in turn, so don't recurse
+    for dir in dirs {
+        check_new_dir(dir, packagedb, reports, false);
+    }
 }
 
-fn check_new_dir(dir: PathBuf, packagedb: &PackageDb, reports: &mut Report) {
+fn check_new_dir(dir: PathBuf, packagedb: &PackageDb, reports: &mut Report, recurse: bool) {
     match fs::read_dir(&dir) {
         Ok(ents) => {
             let mut ents = ents
@@ -39,7 +77,7 @@ fn check_new_dir(dir: PathBuf, packagedb: &PackageDb, reports: &mut Report) {
             ents.sort();
 
             for ent in ents {
-                check_new_ent(ent, packagedb, reports);
+                check_new_ent(ent, packagedb, reports, recurse);
             }
         }
         Err(e) => {
@@ -48,17 +86,14 @@ fn check_new_dir(dir: PathBuf, packagedb: &PackageDb, reports: &mut Report) {
     }
 }
 
-fn check_new_ent(ent: PathBuf, packagedb: &PackageDb, reports: &mut Report) {
-    let cpath = match canonicalize(&ent) {
-        Ok(path) => path,
-        _ => ent.clone(),
-    };
+fn check_new_ent(ent: PathBuf, packagedb: &PackageDb, reports: &mut Report, recurse: bool) {
+    let cpath = packagedb.canonical(&ent);
 
     if packagedb.find_canonical(&cpath) {
-        if should_recurse(&ent) {
-            check_new_dir(ent, packagedb, reports);
+        if recurse && !packagedb.find_canonical_tree(&cpath) && should_recurse(&ent) {
+            check_new_dir(ent, packagedb, reports, true);
         }
-    } else {
+    } else if !linked_to_owned(packagedb, &ent) {
         let mode = match ent.symlink_metadata() {
             Ok(meta) => meta.st_mode(),
             _ => 0,
@@ -68,35 +103,47 @@ fn check_new_ent(ent: PathBuf, packagedb: &PackageDb, reports: &mut Report) {
     }
 }
 
-fn should_recurse(ent: &Path) -> bool {
-    let mut recurse = false;
+/// Unpackaged symlinks to packaged files are treated as owned
+pub fn linked_to_owned(packagedb: &PackageDb, ent: &Path) -> bool {
+    ent.is_symlink() && canonicalize(ent).is_ok_and(|target| packagedb.find_canonical(&target))
+}
 
-    if ent.is_dir() && !ent.is_symlink() {
-        recurse = true;
+pub fn should_recurse(ent: &Path) -> bool {
+    ent.is_dir() && !ent.is_symlink() && skipped_filesystem(ent).is_none()
+}
 
-        // Convert path to CStr
-        let cstr = CString::new(ent.as_os_str().as_bytes()).unwrap();
+/// Returns the name of the filesystem type if the path is on a virtual filesystem, or a read-only
+/// image filesystem under the snap or flatpak directories, which is not walked
+pub fn skipped_filesystem(ent: &Path) -> Option<&'static str> {
+    // Convert path to CStr
+    let cstr = CString::new(ent.as_os_str().as_bytes()).unwrap();
 
-        // Create statfs64 buffer
-        let mut stat = MaybeUninit::<statfs64>::zeroed();
-        let stat_ptr = stat.as_mut_ptr();
+    // Create statfs64 buffer
+    let mut stat = MaybeUninit::<statfs64>::zeroed();
+    let stat_ptr = stat.as_mut_ptr();
 
-        // Stat the filesystem for the path
-        let rc = unsafe { libc::statfs64(cstr.as_ptr(), stat_ptr) };
+    // Stat the filesystem for the path
+    let rc = unsafe { libc::statfs64(cstr.as_ptr(), stat_ptr) };
 
-        if rc == 0 {
-            let stat = unsafe { stat.assume_init() };
+    if rc != 0 {
+        return None;
+    }
 
-            // Check filesystem type
-            match stat.f_type as libc::c_long {
-                PROC_SUPER_MAGIC | TMPFS_MAGIC | SYSFS_MAGIC | DEBUGFS_MAGIC | TRACEFS_MAGIC
-                | HUGETLBFS_MAGIC | CGROUP_SUPER_MAGIC | CGROUP2_SUPER_MAGIC => {
-                    recurse = false;
-                }
-                _ => (),
-            }
+    let stat = unsafe { stat.assume_init() };
+
+    // Check filesystem type
+    match stat.f_type as libc::c_long {
+        PROC_SUPER_MAGIC => Some("proc"),
+        TMPFS_MAGIC => Some("tmpfs"),
+        SYSFS_MAGIC => Some("sysfs"),
+        DEBUGFS_MAGIC => Some("debugfs"),
+        TRACEFS_MAGIC => Some("tracefs"),
+        HUGETLBFS_MAGIC => Some("hugetlbfs"),
+        CGROUP_SUPER_MAGIC => Some("cgroup"),
+        CGROUP2_SUPER_MAGIC => Some("cgroup2"),
+        SQUASHFS_MAGIC if IMAGE_MOUNT_DIRS.iter().any(|dir| ent.starts_with(dir)) => {
+            Some("squashfs")
         }
+        _ => None,
     }
-
-    recurse
 }
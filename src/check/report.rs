@@ -1,13 +1,18 @@
-use std::path::{Path, PathBuf};
-
-use regex::Regex;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::packageman::{PackageDb, PackageFile};
 
+use super::ignore::Ignore;
+
 pub enum ReportItem {
     Missing(Missing),
     Changed(Changed),
     New(New),
+    Aliased(Aliased),
+    Moved(Moved),
 }
 
 impl ReportItem {
@@ -16,6 +21,23 @@ impl ReportItem {
             ReportItem::Missing(missing) => &missing.path,
             ReportItem::Changed(changed) => &changed.path,
             ReportItem::New(new) => &new.path,
+            ReportItem::Aliased(aliased) => &aliased.path,
+            ReportItem::Moved(moved) => &moved.from,
+        }
+    }
+
+    /// The index of the package a missing or changed file belongs to, and whether the file is a
+    /// configuration file
+    pub fn package_file(&self) -> Option<(usize, bool)> {
+        match self {
+            ReportItem::Missing(missing) => {
+                missing.package.map(|package| (package, missing.config))
+            }
+            ReportItem::Changed(changed) => {
+                changed.package.map(|package| (package, changed.config))
+            }
+            ReportItem::Moved(moved) => moved.package.map(|package| (package, moved.config)),
+            ReportItem::New(_) | ReportItem::Aliased(_) => None,
         }
     }
 }
@@ -39,17 +61,52 @@ impl std::fmt::Display for ReportItem {
                 new.path.display(),
                 unix_mode::to_string(new.mode)
             )),
+            ReportItem::Aliased(aliased) => f.write_fmt(format_args!(
+                "ALIASED {} (package {}, same file as {} in package {})",
+                aliased.path.display(),
+                aliased.rpm,
+                aliased.other.display(),
+                aliased.other_rpm
+            )),
+            ReportItem::Moved(moved) => f.write_fmt(format_args!(
+                "MOVED   {} -> {} (package {})",
+                moved.from.display(),
+                moved.to.display(),
+                moved.rpm
+            )),
         }
     }
 }
 
 pub struct Missing {
     path: PathBuf,
+    package: Option<usize>,
+    config: bool,
     rpm: String,
+    size: Option<usize>,
+    chksum: Option<Vec<u8>>,
+}
+
+impl Missing {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Packaged size
+    pub fn size(&self) -> Option<usize> {
+        self.size
+    }
+
+    /// Packaged checksum
+    pub fn chksum(&self) -> Option<&[u8]> {
+        self.chksum.as_deref()
+    }
 }
 
 pub struct Changed {
     path: PathBuf,
+    package: Option<usize>,
+    config: bool,
     rpm: String,
     desc: String,
 }
@@ -59,31 +116,70 @@ pub struct New {
     mode: u32,
 }
 
+impl New {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+pub struct Aliased {
+    path: PathBuf,
+    rpm: String,
+    other: PathBuf,
+    other_rpm: String,
+}
+
+/// A missing file found as a new file elsewhere
+pub struct Moved {
+    from: PathBuf,
+    to: PathBuf,
+    package: Option<usize>,
+    config: bool,
+    rpm: String,
+}
+
 pub struct Report {
-    ignores: Vec<Regex>,
+    ignores: Vec<Ignore>,
     reports: Vec<ReportItem>,
+    excluded: usize,
 }
 
 impl Report {
-    pub fn new(ignores: Vec<Regex>) -> Self {
+    pub fn new(ignores: Vec<Ignore>) -> Self {
         Self {
             ignores,
             reports: Vec::new(),
+            excluded: 0,
         }
     }
 
     pub fn add_missing(&mut self, packagedb: &PackageDb, file: &PackageFile) {
         self.reports.push(ReportItem::Missing(Missing {
             path: PathBuf::from(file.path()),
+            package: *file.package(),
+            config: file.is_config(),
             rpm: packagedb
                 .package_to_string(*file.package(), false)
                 .to_string(),
+            size: *file.size(),
+            chksum: file.chksum().clone(),
         }))
     }
 
+    /// Counts a missing file which was intentionally never installed
+    pub fn add_excluded(&mut self) {
+        self.excluded += 1;
+    }
+
     pub fn add_change(&mut self, packagedb: &PackageDb, file: &PackageFile, desc: String) {
         self.reports.push(ReportItem::Changed(Changed {
             path: PathBuf::from(file.path()),
+            package: *file.package(),
+            config: file.is_config(),
             rpm: packagedb
                 .package_to_string(*file.package(), false)
                 .to_string(),
@@ -95,21 +191,147 @@ impl Report {
         self.reports.push(ReportItem::New(New { path: file, mode }))
     }
 
+    pub fn add_aliased(&mut self, packagedb: &PackageDb, file: &PackageFile, other: &PackageFile) {
+        self.reports.push(ReportItem::Aliased(Aliased {
+            path: PathBuf::from(file.path()),
+            rpm: packagedb
+                .package_to_string(*file.package(), false)
+                .to_string(),
+            other: PathBuf::from(other.path()),
+            other_rpm: packagedb
+                .package_to_string(*other.package(), false)
+                .to_string(),
+        }))
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &ReportItem> {
+        self.reports.iter()
+    }
+
+    /// Replaces missing items with moved items. Each move is the index of the missing item, the
+    /// index of the new item for the destination if it was reported itself rather than being
+    /// inside a new directory, and the destination
+    pub fn replace_moves(&mut self, moves: Vec<(usize, Option<usize>, PathBuf)>) {
+        let mut reports = self.reports.drain(..).map(Some).collect::<Vec<_>>();
+
+        for (missing, new, to) in moves {
+            let Some(ReportItem::Missing(missing)) = reports[missing].take() else {
+                panic!("Expecting missing report item");
+            };
+
+            if let Some(new) = new {
+                reports[new] = None;
+            }
+
+            self.reports.push(ReportItem::Moved(Moved {
+                from: missing.path,
+                to,
+                package: missing.package,
+                config: missing.config,
+                rpm: missing.rpm,
+            }));
+        }
+
+        self.reports.extend(reports.into_iter().flatten());
+    }
+
+    /// Report items not filtered out by an ignore rule
+    pub fn unfiltered(&self) -> impl Iterator<Item = &ReportItem> {
+        self.reports
+            .iter()
+            .filter(|rep| self.find_ignore(rep.path()).is_none())
+    }
+
+    /// Finds the first ignore rule matching a path
+    pub fn find_ignore(&self, path: &Path) -> Option<&Ignore> {
+        self.ignores.iter().find(|ignore| ignore.is_match(path))
+    }
+
     pub fn sort(&mut self) {
         self.reports.sort_by(|a, b| a.path().cmp(b.path()))
     }
 
     pub fn print(&self, debug: u8) {
         for rep in &self.reports {
-            if !self
-                .ignores
-                .iter()
-                .any(|ignore| ignore.is_match(&rep.path().to_string_lossy()))
-            {
+            if self.find_ignore(rep.path()).is_none() {
                 println!("{rep}");
             } else if debug > 1 {
                 eprintln!("{} filtered out by regex", rep.path().to_string_lossy());
             }
         }
+
+        self.print_excluded();
+    }
+
+    /// Prints the number of missing and changed files for each package, most affected first
+    pub fn print_summary(&self, packagedb: &PackageDb, debug: u8) {
+        // Count files in each package
+        let mut totals: HashMap<Option<usize>, usize> = HashMap::new();
+
+        for file in packagedb.files() {
+            *totals.entry(*file.package()).or_default() += 1;
+        }
+
+        // Count missing and changed files for each package
+        let mut counts: HashMap<Option<usize>, (usize, usize)> = HashMap::new();
+        let mut new = 0;
+
+        for rep in &self.reports {
+            if self.find_ignore(rep.path()).is_some() {
+                if debug > 1 {
+                    eprintln!("{} filtered out by regex", rep.path().to_string_lossy());
+                }
+                continue;
+            }
+
+            match rep {
+                ReportItem::Missing(missing) => counts.entry(missing.package).or_default().0 += 1,
+                ReportItem::Moved(moved) => counts.entry(moved.package).or_default().0 += 1,
+                ReportItem::Changed(changed) => counts.entry(changed.package).or_default().1 += 1,
+                ReportItem::New(_) => new += 1,
+                ReportItem::Aliased(_) => (),
+            }
+        }
+
+        let mut rows = counts
+            .into_iter()
+            .map(|(package, (missing, changed))| {
+                let total = totals.get(&package).copied().unwrap_or_default().max(1);
+                let affected = (missing + changed) as f64 * 100.0 / total as f64;
+
+                (package, missing, changed, total, affected)
+            })
+            .collect::<Vec<_>>();
+
+        // Sort by proportion affected, then number affected, then package
+        rows.sort_by(|a, b| {
+            b.4.total_cmp(&a.4)
+                .then((b.1 + b.2).cmp(&(a.1 + a.2)))
+                .then(a.0.cmp(&b.0))
+        });
+
+        println!("MISSING CHANGED   FILES AFFECTED PACKAGE");
+
+        for (package, missing, changed, total, affected) in rows {
+            println!(
+                "{missing:>7} {changed:>7} {total:>7} {affected:>7.1}% {}",
+                packagedb.package_to_string(package, false)
+            );
+        }
+
+        if new > 0 {
+            println!("{new} new files");
+        }
+
+        self.print_excluded();
+    }
+
+    fn print_excluded(&self) {
+        if self.excluded > 0 {
+            eprintln!(
+                "{} missing files were excluded from installation by configuration",
+                self.excluded
+            );
+        }
     }
 }
@@ -2,17 +2,35 @@ use memmap2::{Advice, Mmap};
 use sha2::{Digest, Sha256};
 use std::{
     error::Error,
-    fs::{symlink_metadata, File, Metadata},
+    fs::{read_link, symlink_metadata, File, Metadata},
     os::unix::fs::MetadataExt,
+    path::Path,
 };
 
-use crate::packageman::{PackageDb, PackageFile};
+use crate::packageman::{file_type_name, FileState, PackageDb, PackageFile};
 
-use super::{report::Report, CheckArgs};
+use super::{nar::nar_digest, report::Report, CheckArgs, MtimeCheck};
 
 pub fn verify(packagedb: &PackageDb, args: &CheckArgs, reports: &mut Report) {
+    verify_files(
+        packagedb,
+        args,
+        reports,
+        packagedb
+            .files()
+            .filter(|file| args.packages.selects(file))
+            .filter(|file| args.in_scope(packagedb, file.path())),
+    );
+}
+
+pub fn verify_files<'a>(
+    packagedb: &PackageDb,
+    args: &CheckArgs,
+    reports: &mut Report,
+    files: impl Iterator<Item = &'a PackageFile>,
+) {
     // Verify files
-    for file in packagedb.files() {
+    for file in files {
         match symlink_metadata(file.path()) {
             Ok(meta) => {
                 if args.changed {
@@ -21,8 +39,13 @@ pub fn verify(packagedb: &PackageDb, args: &CheckArgs, reports: &mut Report) {
             }
             Err(e) => match e.kind() {
                 std::io::ErrorKind::NotFound => {
-                    if args.missing {
-                        reports.add_missing(packagedb, file);
+                    // Ghost files need not exist and replaced files belong to another package
+                    if args.missing && !(file.is_ghost() || file.state() == FileState::Replaced) {
+                        if file.is_excluded() {
+                            reports.add_excluded();
+                        } else {
+                            reports.add_missing(packagedb, file);
+                        }
                     }
                 }
                 _ => eprintln!("ERROR: Failed to stat file {} ({e})", file.path().display()),
@@ -38,6 +61,38 @@ fn verify_file(
     file: &PackageFile,
     meta: Metadata,
 ) {
+    // Directories owned in their entirety only have a NAR digest to check
+    if file.is_tree() {
+        if args.nar && file.chksum().is_some() {
+            verify_nar(packagedb, reports, file);
+        }
+
+        return;
+    }
+
+    // Ghost files have no packaged contents, and files not in the normal state were either not
+    // installed by this package or were replaced by another
+    if file.is_ghost() || file.state() != FileState::Normal {
+        return;
+    }
+
+    // Check for file type change. Nothing else is comparable once the type has changed
+    if let Some(file_type) = packaged_type(file) {
+        if meta.mode() & libc::S_IFMT != file_type {
+            reports.add_change(
+                packagedb,
+                file,
+                format!(
+                    "type from {} to {}",
+                    file_type_name(file_type),
+                    file_type_name(meta.mode())
+                ),
+            );
+
+            return;
+        }
+    }
+
     // Check for mode change
     if let Some(mode) = file.mode() {
         if meta.mode() != *mode {
@@ -55,6 +110,43 @@ fn verify_file(
         }
     }
 
+    // Check for owner change
+    if let Some((uid, gid)) = file.owner() {
+        if meta.uid() != *uid || meta.gid() != *gid {
+            reports.add_change(
+                packagedb,
+                file,
+                format!("owner from {uid}:{gid} to {}:{}", meta.uid(), meta.gid()),
+            );
+
+            return;
+        }
+    }
+
+    // Check symbolic link target
+    if let Some(link) = file.link() {
+        if meta.is_symlink() {
+            match read_link(file.path()) {
+                Ok(target) => {
+                    if target != link {
+                        reports.add_change(
+                            packagedb,
+                            file,
+                            format!(
+                                "link target from {} to {}",
+                                link.display(),
+                                target.display()
+                            ),
+                        );
+
+                        return;
+                    }
+                }
+                Err(e) => eprintln!("ERROR: Failed to read link {} ({e})", file.path().display()),
+            }
+        }
+    }
+
     // Check file size
     if let Some(size) = file.size() {
         if meta.size() != *size as u64 {
@@ -86,47 +178,116 @@ fn verify_file(
 
     // Check modification date for regular files
     if meta.is_file() {
-        if let Some(mtime) = file.time() {
-            if meta.mtime() > *mtime {
-                reports.add_change(packagedb, file, String::from("Modification time later"));
-                #[allow(clippy::needless_return)]
-                return;
+        if let Some(desc) = check_mtime(args, file, meta.mtime()) {
+            reports.add_change(packagedb, file, desc);
+        }
+    }
+}
+
+/// Default tolerance when comparing with the package install time, allowing for files written
+/// by maintainer scripts after the package was unpacked
+const INSTALLED_MTIME_TOLERANCE: i64 = 3600;
+
+/// Compares a file's modification time with the packaged time, returning a description of any
+/// difference outside the tolerance
+fn check_mtime(args: &CheckArgs, file: &PackageFile, mtime: i64) -> Option<String> {
+    let tolerance = args.mtime_tolerance.unwrap_or(0);
+
+    match args.mtime {
+        MtimeCheck::Off => None,
+        MtimeCheck::Newer => match (*file.time(), *file.installed()) {
+            (Some(time), _) => {
+                (mtime > time + tolerance).then(|| String::from("Modification time later"))
+            }
+            // Without a per-file time the file shouldn't have changed since it was installed
+            (None, Some(installed)) => {
+                let tolerance = args.mtime_tolerance.unwrap_or(INSTALLED_MTIME_TOLERANCE);
+
+                (mtime > installed + tolerance).then(|| String::from("Modification time later"))
+            }
+            (None, None) => None,
+        },
+        MtimeCheck::Any => {
+            let time = (*file.time())?;
+
+            if mtime > time + tolerance {
+                Some(String::from("Modification time later"))
+            } else if mtime < time - tolerance {
+                Some(String::from("Modification time earlier"))
+            } else {
+                None
             }
         }
     }
 }
 
+/// The packaged file type, inferred from the packaged details if there is no mode
+fn packaged_type(file: &PackageFile) -> Option<u32> {
+    match file.mode() {
+        Some(mode) => Some(mode & libc::S_IFMT),
+        None if file.chksum().is_some() || file.size().is_some() => Some(libc::S_IFREG),
+        None if file.link().is_some() => Some(libc::S_IFLNK),
+        None => None,
+    }
+}
+
+fn verify_nar(packagedb: &PackageDb, reports: &mut Report, file: &PackageFile) {
+    match nar_digest(file.path()) {
+        Ok((size, digest)) => {
+            if let Some(expected) = file.size() {
+                if size != *expected as u64 {
+                    reports.add_change(
+                        packagedb,
+                        file,
+                        format!("NAR size from {} to {}", expected, size),
+                    );
+
+                    return;
+                }
+            }
+
+            if file.chksum().as_ref() != Some(&digest) {
+                reports.add_change(packagedb, file, String::from("NAR hash changed"));
+            }
+        }
+        Err(e) => eprintln!(
+            "ERROR: Failed to calculate NAR hash for {} ({e})",
+            file.path().display()
+        ),
+    }
+}
+
 fn check_digest(package_file: &PackageFile) -> Result<bool, Box<dyn Error>> {
     let chksum = package_file.chksum().as_ref().unwrap();
 
-    let hasher: Box<dyn Fn(Mmap) -> bool> = match chksum.len() {
+    Ok(file_digest(package_file.path(), chksum.len())? == *chksum)
+}
+
+/// Hashes a file with the algorithm giving a digest of the length (MD5 or SHA256)
+pub fn file_digest(path: &Path, len: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let hasher: Box<dyn Fn(Mmap) -> Vec<u8>> = match len {
         16 => {
             // MD5
-            Box::new(|bytes| -> bool {
-                let digest: [u8; 16] = md5::compute(bytes).into();
-                digest == chksum.as_slice()
-            })
+            Box::new(|bytes| -> Vec<u8> { md5::compute(bytes).to_vec() })
         }
         32 => {
             // SHA256
-            Box::new(|bytes| -> bool {
+            Box::new(|bytes| -> Vec<u8> {
                 let mut hasher = Sha256::new();
                 hasher.update(bytes);
-                let hash = hasher.finalize();
-
-                hash[..] == *chksum
+                hasher.finalize().to_vec()
             })
         }
         len => Err(format!("ERROR: Unknown hash length {}", len))?,
     };
 
     // Open the file
-    let file = File::open(package_file.path())?;
+    let file = File::open(path)?;
 
     // Mem map the file
     let mmap = unsafe { Mmap::map(&file)? };
     let _ = mmap.advise(Advice::Sequential);
 
-    // Hash the file and check
+    // Hash the file
     Ok(hasher(mmap))
 }
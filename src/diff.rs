@@ -0,0 +1,87 @@
+use std::{
+    error::Error,
+    fs,
+    io::{stdout, Write},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use similar::TextDiff;
+
+use crate::{
+    packageman::{file_type_name, PackageDb},
+    restore::{cached_member, for_each_path},
+};
+
+/// Prints a unified diff of each path against the packaged copy in the package manager's
+/// archive cache
+pub fn diff(packagedb: &PackageDb, paths: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    for_each_path(paths, "compare", |path| diff_file(packagedb, path))
+}
+
+/// Prints the differences between a file and its packaged copy
+fn diff_file(packagedb: &PackageDb, path: &Path) -> Result<(), Box<dyn Error>> {
+    let member = cached_member(packagedb, path)?;
+
+    let metadata = path.symlink_metadata()?;
+
+    let packaged_type = member.mode & libc::S_IFMT;
+
+    if metadata.mode() & libc::S_IFMT != packaged_type {
+        println!(
+            "{}: packaged as {}, now {}",
+            path.display(),
+            file_type_name(member.mode),
+            file_type_name(metadata.mode())
+        );
+        return Ok(());
+    }
+
+    match packaged_type {
+        libc::S_IFREG => {
+            let current = fs::read(path)?;
+
+            if current == member.data {
+                return Ok(());
+            }
+
+            match (
+                std::str::from_utf8(&member.data),
+                std::str::from_utf8(&current),
+            ) {
+                (Ok(packaged), Ok(current)) => {
+                    let old = format!("{} (packaged)", path.display());
+                    let new = path.display().to_string();
+
+                    write!(
+                        stdout().lock(),
+                        "{}",
+                        TextDiff::from_lines(packaged, current)
+                            .unified_diff()
+                            .context_radius(3)
+                            .header(&old, &new)
+                    )?;
+                }
+                _ => println!("Binary file {} differs from packaged copy", path.display()),
+            }
+        }
+        libc::S_IFLNK => {
+            let current = fs::read_link(path)?;
+
+            if let Some(packaged) = member.link.filter(|link| *link != current) {
+                println!(
+                    "{}: symbolic link to {}, packaged as link to {}",
+                    path.display(),
+                    current.display(),
+                    packaged.display()
+                );
+            }
+        }
+        _ => Err(format!(
+            "can't compare {}",
+            unix_mode::to_string(member.mode)
+        ))?,
+    }
+
+    Ok(())
+}
@@ -1,11 +1,22 @@
-use check::{check, CheckArgs};
+use check::{check, explain, run_check, CheckArgs, MtimeCheck, PackageFilter};
 use clap::{ArgAction, Parser, Subcommand};
-use packageman::{PackageDb, PackageMgr};
+use diff::diff;
+use owns::owns;
+use packageman::{
+    normalise_path, tarball_manifest, LoadArgs, PackageDb, PackageMgr, Source, TarballSpec,
+};
 use regex::{escape, Regex};
-use std::{borrow::Cow, error::Error};
+use repair::repair_plan;
+use restore::restore;
+use std::{borrow::Cow, env, error::Error, io::stdout, path::PathBuf};
 
+mod archive;
 mod check;
+mod diff;
+mod owns;
 mod packageman;
+mod repair;
+mod restore;
 
 #[derive(Parser, Clone)]
 #[command(version, about, long_about = None)]
@@ -17,6 +28,30 @@ struct Cli {
     #[arg(short = 'p', long)]
     manager: Option<PackageMgr>,
 
+    /// Secondary ownership source (default all detected)
+    #[arg(short = 'S', long)]
+    source: Vec<Source>,
+
+    /// Don't load secondary ownership sources
+    #[arg(long, conflicts_with = "source")]
+    no_sources: bool,
+
+    /// Load additional packages from the JSON output of a command
+    #[arg(short = 'x', long, value_name = "COMMAND")]
+    external_cmd: Vec<String>,
+
+    /// Load additional packages from a JSON manifest file
+    #[arg(short = 'X', long, value_name = "FILE")]
+    external_file: Vec<PathBuf>,
+
+    /// Load a tar archive extracted in to PREFIX (default /) as a package
+    #[arg(short = 't', long, value_name = "ARCHIVE[=PREFIX]")]
+    tarball: Vec<TarballSpec>,
+
+    /// Read file modes, sizes, owners and link targets from cached .deb archives (apt only)
+    #[arg(long)]
+    deb_metadata: bool,
+
     /// Print debugging messages
     #[arg(short = 'd', long, action = ArgAction::Count)]
     debug: u8,
@@ -30,6 +65,18 @@ enum Commands {
     Packages,
     /// Prints a list of files in the package manager database
     Files,
+    /// Prints an external package manifest for a tar archive
+    Manifest(Manifest),
+    /// Prints the package owning each path
+    Owns(Owns),
+    /// Explains why a path is or isn't reported by check
+    Explain(Explain),
+    /// Prints the commands to reinstall packages with missing or changed files
+    RepairPlan(RepairPlan),
+    /// Extracts the packaged copy of files from the package manager's archive cache
+    Restore(Restore),
+    /// Prints a unified diff of files against the packaged copy in the archive cache
+    Diff(Diff),
 }
 
 #[derive(Parser, Clone, Default)]
@@ -50,6 +97,39 @@ struct Check {
     #[arg(short = 's', long)]
     checksum: bool,
 
+    /// Verify Nix store path NAR hashes
+    #[arg(long)]
+    nar: bool,
+
+    /// How to compare file modification times with the packaged times
+    #[arg(long, value_enum, default_value_t)]
+    mtime: MtimeCheck,
+
+    /// Ignore modification time differences of up to this many seconds [default: 0, or 3600 when
+    /// comparing with the package install time]
+    #[arg(long, value_name = "SECONDS")]
+    mtime_tolerance: Option<u32>,
+
+    /// Only check packages with names matching a glob
+    #[arg(long, value_name = "GLOB")]
+    package: Vec<String>,
+
+    /// Don't check packages with names matching a glob
+    #[arg(long, value_name = "GLOB")]
+    exclude_package: Vec<String>,
+
+    /// Only look for new files in directories owned by the checked packages
+    #[arg(long)]
+    package_dirs: bool,
+
+    /// Only check files in this directory
+    #[arg(long, value_name = "DIR")]
+    path: Vec<PathBuf>,
+
+    /// Print a summary of missing and changed files for each package
+    #[arg(long)]
+    summary: bool,
+
     /// Ignore directory
     #[clap(short = 'i', long)]
     pub ignore_dir: Vec<String>,
@@ -63,6 +143,53 @@ struct Check {
     pub ignore_regex: Vec<String>,
 }
 
+#[derive(Parser, Clone)]
+struct Manifest {
+    /// Tar archive and the directory it is extracted in to (default /)
+    #[arg(value_name = "ARCHIVE[=PREFIX]")]
+    tarball: TarballSpec,
+}
+
+#[derive(Parser, Clone)]
+struct Owns {
+    /// Paths to look up
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Parser, Clone)]
+struct Explain {
+    #[command(flatten)]
+    check: Check,
+
+    /// Path to explain
+    path: PathBuf,
+}
+
+#[derive(Parser, Clone)]
+struct RepairPlan {
+    #[command(flatten)]
+    check: Check,
+}
+
+#[derive(Parser, Clone)]
+struct Restore {
+    /// Write files under this directory instead of beside the original with a .orig suffix
+    #[arg(long, value_name = "DIR")]
+    to: Option<PathBuf>,
+
+    /// Paths to restore
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Parser, Clone)]
+struct Diff {
+    /// Paths to compare
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
 
@@ -107,56 +234,169 @@ fn main() -> Result<(), Box<dyn Error>> {
                 )
             }
         }
-        Commands::Check(checkargs) => {
-            // Check packages
+        Commands::Manifest(manifestargs) => {
+            // Write tarball manifest
+            tarball_manifest(&manifestargs.tarball, &mut stdout().lock(), cli.debug)?;
+        }
+        Commands::Owns(ownsargs) => {
+            // Look up path owners
 
             // Load package database
             let packagedb = load_packages(&cli)?;
 
-            // Build ignore regular expression list
-            let ignores = checkargs
-                .ignore_regex
-                .iter()
-                .cloned()
-                .chain(
-                    checkargs
-                        .ignore_dir
-                        .iter()
-                        .map(|dir| format!("^{}($|/.*)", escape(dir))),
-                )
-                .chain(
-                    checkargs
-                        .ignore_file
-                        .iter()
-                        .map(|file| format!("^{}$", escape(file))),
-                )
-                .collect::<Vec<_>>();
+            owns(&packagedb, &ownsargs.paths)?;
+        }
+        Commands::Explain(explainargs) => {
+            // Explain a check decision
 
-            // Report differences
-            check(
+            // Load package database
+            let packagedb = load_packages(&cli)?;
+
+            // Make path absolute
+            let path = normalise_path(&env::current_dir()?.join(&explainargs.path));
+
+            explain(
                 &packagedb,
-                CheckArgs {
-                    changed: !checkargs.no_changed,
-                    missing: !checkargs.no_missing,
-                    new: !checkargs.no_new,
-                    checksum: checkargs.checksum,
-                    ignores,
-                    debug: cli.debug,
-                },
+                check_args(&packagedb, &explainargs.check, cli.debug)?,
+                &path,
             );
         }
+        Commands::RepairPlan(repairargs) => {
+            // Plan package reinstalls
+
+            // Load package database
+            let packagedb = load_packages(&cli)?;
+
+            // New files don't affect the plan
+            let args = CheckArgs {
+                new: false,
+                ..check_args(&packagedb, &repairargs.check, cli.debug)?
+            };
+
+            repair_plan(&packagedb, &run_check(&packagedb, &args));
+        }
+        Commands::Restore(restoreargs) => {
+            // Extract packaged copies of files
+
+            // Load package database
+            let packagedb = load_packages(&cli)?;
+
+            restore(&packagedb, &restoreargs.paths, restoreargs.to.as_deref())?;
+        }
+        Commands::Diff(diffargs) => {
+            // Compare files with packaged copies
+
+            // Load package database
+            let packagedb = load_packages(&cli)?;
+
+            diff(&packagedb, &diffargs.paths)?;
+        }
+        Commands::Check(checkargs) => {
+            // Check packages
+
+            // Load package database
+            let packagedb = load_packages(&cli)?;
+
+            // Report differences
+            check(&packagedb, check_args(&packagedb, checkargs, cli.debug)?);
+        }
     }
 
     Ok(())
 }
 
+fn check_args(
+    packagedb: &PackageDb,
+    checkargs: &Check,
+    debug: u8,
+) -> Result<CheckArgs, Box<dyn Error>> {
+    // Build ignore regular expression list
+    let ignores = checkargs
+        .ignore_regex
+        .iter()
+        .cloned()
+        .chain(
+            checkargs
+                .ignore_dir
+                .iter()
+                .map(|dir| format!("^{}($|/.*)", escape(dir))),
+        )
+        .chain(
+            checkargs
+                .ignore_file
+                .iter()
+                .map(|file| format!("^{}$", escape(file))),
+        )
+        .collect::<Vec<_>>();
+
+    // Build package filter
+    let packages = PackageFilter::new(packagedb, &checkargs.package, &checkargs.exclude_package)?;
+
+    // Make subtrees absolute, resolve directory aliases and drop any inside another
+    let cwd = env::current_dir()?;
+
+    let mut paths = checkargs
+        .path
+        .iter()
+        .map(|path| {
+            packagedb
+                .canonical(&normalise_path(&cwd.join(path)))
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+
+    paths.sort();
+
+    let mut scopes: Vec<PathBuf> = Vec::new();
+
+    for path in paths {
+        if !scopes.iter().any(|scope| path.starts_with(scope)) {
+            scopes.push(path);
+        }
+    }
+
+    Ok(CheckArgs {
+        changed: !checkargs.no_changed,
+        missing: !checkargs.no_missing,
+        new: !checkargs.no_new,
+        checksum: checkargs.checksum,
+        nar: checkargs.nar,
+        mtime: checkargs.mtime,
+        mtime_tolerance: checkargs.mtime_tolerance.map(i64::from),
+        packages,
+        package_dirs: checkargs.package_dirs,
+        paths: scopes,
+        summary: checkargs.summary,
+        ignores,
+        debug,
+    })
+}
+
 fn load_packages(cli: &Cli) -> Result<PackageDb, Box<dyn Error>> {
     let mgr = match &cli.manager {
-        Some(mgr) => mgr.clone(),
+        Some(mgr) => *mgr,
         None => PackageDb::detect_mgr()?,
     };
 
-    PackageDb::load(mgr, cli.debug)
+    let sources = if cli.no_sources {
+        Vec::new()
+    } else if cli.source.is_empty() {
+        PackageDb::detect_sources()
+    } else {
+        cli.source.clone()
+    };
+
+    PackageDb::load(
+        mgr,
+        &LoadArgs {
+            sources,
+            external_cmds: cli.external_cmd.clone(),
+            external_files: cli.external_file.clone(),
+            tarballs: cli.tarball.clone(),
+            deb_metadata: cli.deb_metadata,
+            debug: cli.debug,
+        },
+    )
 }
 
 fn validate_regex(s: &str) -> Result<String, regex::Error> {
@@ -0,0 +1,98 @@
+use std::{
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    check::compile_ignores,
+    packageman::{normalise_path, PackageDb},
+};
+
+/// Prints the owner of each path, or why it has none
+pub fn owns(packagedb: &PackageDb, paths: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let ignores = compile_ignores(packagedb, &[]);
+
+    let cwd = env::current_dir()?;
+
+    for path in paths {
+        // Make absolute and lexically normalise as check does
+        let path = normalise_path(&cwd.join(path));
+
+        let mut reasons = vec![ownership(packagedb, &path)];
+
+        if let Some(ignore) = ignores.iter().find(|ignore| ignore.is_match(&path)) {
+            reasons.push(format!(
+                "ignored by {} rule {}",
+                ignore.source(),
+                ignore.as_str()
+            ));
+        }
+
+        println!("{}: {}", path.display(), reasons.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Maximum number of owners of a shared path to list
+const MAX_OWNERS: usize = 3;
+
+/// Describes the ownership of an absolute path
+pub fn ownership(packagedb: &PackageDb, path: &Path) -> String {
+    let cpath = packagedb.canonical(path);
+
+    // Parent directories filled in for packaged files have no package
+    let owners = |path: &Path| {
+        let mut owners = packagedb
+            .find_files(path)
+            .iter()
+            .filter(|file| file.package().is_some())
+            .map(|file| packagedb.package_to_string(*file.package(), false))
+            .collect::<Vec<_>>();
+
+        owners.sort();
+        owners.dedup();
+
+        owners
+    };
+
+    let path_owners = owners(path);
+
+    if !path_owners.is_empty() {
+        format!("owned by {}", owner_list(&path_owners))
+    } else if packagedb.find_canonical(&cpath) {
+        String::from("target of a packaged symbolic link")
+    } else if let Some(tree) = packagedb.find_containing_tree(&cpath) {
+        format!(
+            "inside {} owned by {}",
+            tree.path().display(),
+            packagedb.package_to_string(*tree.package(), false)
+        )
+    } else if let Some((dir, dir_owners)) = path
+        .ancestors()
+        .skip(1)
+        .map(|dir| (dir, owners(dir)))
+        .find(|(_, dir_owners)| !dir_owners.is_empty())
+    {
+        format!(
+            "not owned, in directory {} owned by {}",
+            dir.display(),
+            owner_list(&dir_owners)
+        )
+    } else {
+        String::from("not owned")
+    }
+}
+
+/// Lists the first few owners, counting the rest
+fn owner_list(owners: &[String]) -> String {
+    let more = owners.len().saturating_sub(MAX_OWNERS);
+
+    let list = owners[..owners.len() - more].join(", ");
+
+    match more {
+        0 => list,
+        more => format!("{list} and {more} other packages"),
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    borrow::Cow,
+    fs::{self, canonicalize},
+    path::{Path, PathBuf},
+};
+
+/// Directories scanned for directory aliases (eg. /bin -> usr/bin, /usr/sbin -> bin)
+const ALIAS_DIRS: [&str; 2] = ["/", "/usr"];
+
+/// Maps symbolically linked directories near the root of the filesystem to the directories
+/// they point to, so paths can be resolved without a canonicalize call per path
+pub struct Aliases {
+    aliases: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Aliases {
+    pub fn load(debug: u8) -> Self {
+        let mut aliases = Vec::new();
+
+        for dir in ALIAS_DIRS {
+            let Ok(ents) = fs::read_dir(dir) else {
+                continue;
+            };
+
+            for ent in ents.filter_map(|ent| ent.ok()) {
+                let path = ent.path();
+
+                if !path.is_symlink() || !path.is_dir() {
+                    continue;
+                }
+
+                // Resolve the full chain of links once
+                match canonicalize(&path) {
+                    Ok(target) if target != path => {
+                        if debug > 1 {
+                            eprintln!(
+                                "Directory {} is an alias of {}",
+                                path.display(),
+                                target.display()
+                            );
+                        }
+
+                        aliases.push((path, target));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        Self { aliases }
+    }
+
+    /// Replaces an aliased directory prefix with the directory it points to
+    pub fn resolve<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        for (alias, target) in &self.aliases {
+            if let Ok(rest) = path.strip_prefix(alias) {
+                if rest.as_os_str().is_empty() {
+                    return Cow::Owned(target.clone());
+                }
+
+                return Cow::Owned(target.join(rest));
+            }
+        }
+
+        Cow::Borrowed(path)
+    }
+
+    /// Returns true if the path is itself one of the aliases
+    pub fn is_alias(&self, path: &Path) -> bool {
+        self.aliases.iter().any(|(alias, _)| alias == path)
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use libc::S_IFLNK;
+
+use super::PackageFile;
+
+/// Directory holding the alternatives symbolic links
+const LINK_DIR: &str = "/etc/alternatives";
+
+/// An alternatives group as recorded in the administrative directory
+struct Group {
+    name: String,
+    link: PathBuf,
+    slaves: Vec<(String, PathBuf)>,
+    choices: Vec<Choice>,
+}
+
+/// One of the alternatives available for a group
+struct Choice {
+    path: PathBuf,
+    slaves: Vec<Option<PathBuf>>,
+}
+
+/// Builds the symbolic links managed by update-alternatives for the currently selected
+/// alternatives, attributed to the package owning the selected alternative
+pub fn load_alternatives(admin_dir: &Path, files: &[PackageFile], debug: u8) -> Vec<PackageFile> {
+    let mut alt_files = Vec::new();
+
+    let Ok(dir) = fs::read_dir(admin_dir) else {
+        return alt_files;
+    };
+
+    if debug > 0 {
+        eprintln!("Reading alternatives from {}", admin_dir.display());
+    }
+
+    // Map packaged paths to their packages
+    let owners = files
+        .iter()
+        .map(|file| (file.path(), *file.package()))
+        .collect::<HashMap<_, _>>();
+
+    let mut added = HashSet::new();
+
+    let mut names = dir
+        .filter_map(|ent| ent.ok())
+        .filter_map(|ent| ent.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    for name in names {
+        let group_file = admin_dir.join(&name);
+
+        let group = match read_group(&group_file, name) {
+            Ok(group) => group,
+            Err(e) => {
+                eprintln!(
+                    "ERROR: Failed to read alternative {} ({e})",
+                    group_file.display()
+                );
+                continue;
+            }
+        };
+
+        // Find the selected alternative
+        let link_dir = Path::new(LINK_DIR);
+
+        let Ok(selected) = fs::read_link(link_dir.join(&group.name)) else {
+            if debug > 1 {
+                eprintln!("No alternative selected for {}", group.name);
+            }
+            continue;
+        };
+
+        let Some(choice) = group.choices.iter().find(|choice| choice.path == selected) else {
+            if debug > 1 {
+                eprintln!(
+                    "Selected alternative {} for {} not registered",
+                    selected.display(),
+                    group.name
+                );
+            }
+            continue;
+        };
+
+        let package = owners
+            .get(choice.path.as_path())
+            .copied()
+            .unwrap_or_default();
+
+        if debug > 1 {
+            eprintln!(
+                "Alternative {} selected as {}",
+                group.name,
+                choice.path.display()
+            );
+        }
+
+        // Master link and link in the alternatives directory
+        let mut links = vec![(group.name.as_str(), &group.link)];
+
+        // Slave links provided by the selected alternative
+        links.extend(
+            group
+                .slaves
+                .iter()
+                .zip(&choice.slaves)
+                .filter(|(_, path)| path.is_some())
+                .map(|((name, link), _)| (name.as_str(), link)),
+        );
+
+        for (name, link) in links {
+            for path in [link.clone(), link_dir.join(name)] {
+                if owners.contains_key(path.as_path()) || !added.insert(path.clone()) {
+                    continue;
+                }
+
+                alt_files.push(PackageFile::new(
+                    path,
+                    package,
+                    None,
+                    Some(S_IFLNK | 0o777),
+                    None,
+                    None,
+                ));
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} alternatives links found", alt_files.len());
+    }
+
+    alt_files
+}
+
+/// Parses an alternatives group file. The format, shared by dpkg and chkconfig, is the mode, the
+/// master link, slave name and link pairs terminated by a blank line, then for each alternative
+/// its path, priority and one line per slave (blank if not provided)
+fn read_group(file: &Path, name: String) -> Result<Group, Box<dyn Error>> {
+    let contents = fs::read_to_string(file)?;
+
+    let mut lines = contents.lines();
+
+    let _mode = lines.next().ok_or("Missing mode")?;
+    let link = PathBuf::from(lines.next().ok_or("Missing link")?);
+
+    let mut slaves = Vec::new();
+
+    loop {
+        match lines.next() {
+            None | Some("") => break,
+            Some(slave_name) => {
+                let slave_link = lines.next().ok_or("Missing slave link")?;
+                slaves.push((slave_name.to_string(), PathBuf::from(slave_link)));
+            }
+        }
+    }
+
+    let mut choices = Vec::new();
+
+    loop {
+        match lines.next() {
+            None | Some("") => break,
+            Some(path) => {
+                let _priority = lines.next().ok_or("Missing priority")?;
+
+                let choice_slaves = (0..slaves.len())
+                    .map(|_| match lines.next() {
+                        None | Some("") => None,
+                        Some(slave) => Some(PathBuf::from(slave)),
+                    })
+                    .collect();
+
+                choices.push(Choice {
+                    path: PathBuf::from(path),
+                    slaves: choice_slaves,
+                });
+            }
+        }
+    }
+
+    Ok(Group {
+        name,
+        link,
+        slaves,
+        choices,
+    })
+}
@@ -0,0 +1,40 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use crate::packageman::Package;
+
+/// dpkg per-package information directory
+const INFO_DIR: &str = "/var/lib/dpkg/info";
+
+/// Reads the configuration files of each package, returning the package index and path
+pub fn read_conffiles(packages: &[Package]) -> HashSet<(usize, PathBuf)> {
+    let mut conffiles = HashSet::new();
+
+    for (package_elem, package) in packages.iter().enumerate() {
+        // Multi-arch packages have the architecture in the file name
+        let contents = [
+            format!("{INFO_DIR}/{}.conffiles", package.namestr()),
+            format!(
+                "{INFO_DIR}/{}:{}.conffiles",
+                package.namestr(),
+                package.archstr()
+            ),
+        ]
+        .into_iter()
+        .find_map(|file| fs::read_to_string(file).ok());
+
+        let Some(contents) = contents else {
+            continue;
+        };
+
+        for line in contents.lines() {
+            // Entries may be preceded by flags such as remove-on-upgrade
+            let Some(start) = line.find('/') else {
+                continue;
+            };
+
+            conffiles.insert((package_elem, PathBuf::from(&line[start..])));
+        }
+    }
+
+    conffiles
+}
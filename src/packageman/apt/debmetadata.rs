@@ -0,0 +1,98 @@
+use rayon::prelude::*;
+use std::{collections::HashMap, error::Error, fs::File, io::BufReader, path::Path};
+
+use crate::{
+    archive::{ar_member, decompress},
+    packageman::{tarball::tar_files, Package, PackageFile},
+};
+
+use super::{cached_deb, statoverride::read_statoverrides};
+
+/// Fills in the file modes, sizes, times, owners and symbolic link targets which dpkg doesn't
+/// record from the data.tar of each package's cached .deb
+pub fn read_deb_metadata(packages: &[Package], files: &mut [PackageFile], debug: u8) {
+    let mut metadata = packages
+        .par_iter()
+        .enumerate()
+        .filter_map(|(package_elem, package)| {
+            let deb = cached_deb(package)?;
+
+            match deb_files(&deb, package_elem, debug) {
+                Ok(deb_files) => Some(deb_files),
+                Err(e) => {
+                    eprintln!("ERROR: Failed to read {} ({e})", deb.display());
+                    None
+                }
+            }
+        })
+        .flatten()
+        .map(|file| ((*file.package(), file.path().to_path_buf()), file))
+        .collect::<HashMap<_, _>>();
+
+    if debug > 0 {
+        eprintln!("{} files found in cached .deb archives", metadata.len());
+    }
+
+    let mut filled = 0;
+
+    for file in files.iter_mut() {
+        if let Some(deb_file) = metadata.remove(&(*file.package(), file.path().to_path_buf())) {
+            file.fill_from(deb_file);
+            filled += 1;
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{filled} files updated from cached .deb archives");
+    }
+
+    // Apply owners and modes changed by dpkg-statoverride
+    match read_statoverrides() {
+        Ok(overrides) => {
+            if debug > 0 {
+                eprintln!("{} dpkg stat overrides found", overrides.len());
+            }
+
+            for file in files.iter_mut() {
+                let Some(mode) = *file.mode() else {
+                    continue;
+                };
+
+                if let Some(over) = overrides.get(file.path()) {
+                    file.set_owner(Some(over.owner));
+                    file.set_mode((mode & libc::S_IFMT) | over.mode);
+                }
+            }
+        }
+        Err(e) => {
+            if debug > 0 {
+                eprintln!("Unable to read dpkg stat overrides ({e})");
+            }
+        }
+    }
+}
+
+/// Reads the files in the data.tar member of a .deb
+fn deb_files(
+    deb: &Path,
+    package_elem: usize,
+    debug: u8,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    if debug > 1 {
+        eprintln!("Reading {}", deb.display());
+    }
+
+    let reader = BufReader::new(File::open(deb)?);
+
+    match ar_member(reader, "data.tar")? {
+        // dpkg's MD5 sums are used in preference, so the contents aren't hashed
+        Some(data) => tar_files(
+            decompress(data)?,
+            Path::new("/"),
+            package_elem,
+            false,
+            debug,
+        ),
+        None => Err("No data.tar member found")?,
+    }
+}
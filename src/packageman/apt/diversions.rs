@@ -0,0 +1,39 @@
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+/// dpkg database of diverted files
+const DIVERSIONS_FILE: &str = "/var/lib/dpkg/diversions";
+
+/// A file diverted by dpkg-divert
+pub struct Diversion {
+    /// Path the diverted file is installed to
+    pub to: PathBuf,
+    /// Package making the diversion, or None for a local diversion
+    pub package: Option<String>,
+}
+
+/// Reads the dpkg diversions database, keyed by the original path. Each diversion is three lines:
+/// the original path, the diverted path and the diverting package (":" for local diversions)
+pub fn read_diversions() -> Result<HashMap<PathBuf, Diversion>, Box<dyn Error>> {
+    let contents = fs::read_to_string(DIVERSIONS_FILE)?;
+
+    let mut diversions = HashMap::new();
+    let mut lines = contents.lines();
+
+    while let Some(from) = lines.next() {
+        let to = lines.next().ok_or("Missing diverted path")?;
+        let package = lines.next().ok_or("Missing diverting package")?;
+
+        diversions.insert(
+            PathBuf::from(from),
+            Diversion {
+                to: PathBuf::from(to),
+                package: match package {
+                    ":" => None,
+                    package => Some(package.to_string()),
+                },
+            },
+        );
+    }
+
+    Ok(diversions)
+}
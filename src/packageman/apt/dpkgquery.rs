@@ -1,4 +1,4 @@
-use crate::packageman::{Package, PackageFile};
+use crate::packageman::{Origin, Package, PackageFile, PackageMgr};
 use rayon::prelude::*;
 use std::error::Error;
 use std::ffi::{OsStr, OsString};
@@ -85,8 +85,8 @@ pub fn dpkg_query(debug: u8) -> Result<(Vec<Package>, Vec<PackageFile>), Box<dyn
             fullname.push(":");
             fullname.push(&arch);
 
-            // Get modification time
-            let mtime = Some(
+            // Get install time (dpkg doesn't record per-file modification times)
+            let installed = Some(
                 std::str::from_utf8(split.next().unwrap())
                     .unwrap()
                     .parse::<i64>()
@@ -99,7 +99,13 @@ pub fn dpkg_query(debug: u8) -> Result<(Vec<Package>, Vec<PackageFile>), Box<dyn
             // Add to package list
             let mut packages = packages_mutex.lock().unwrap();
 
-            packages.push(Package::new(fullname, name, version, Some(arch)));
+            packages.push(Package::new(
+                fullname,
+                name,
+                version,
+                Some(arch),
+                Origin::Mgr(PackageMgr::Apt),
+            ));
             let package_elem = packages.len() - 1;
 
             drop(packages);
@@ -120,14 +126,18 @@ pub fn dpkg_query(debug: u8) -> Result<(Vec<Package>, Vec<PackageFile>), Box<dyn
                     }
 
                     // Add file
-                    files.push(PackageFile::new(
+                    let mut file = PackageFile::new(
                         PathBuf::from(line),
                         Some(package_elem),
                         None,
                         None,
                         chksum,
-                        mtime,
-                    ));
+                        None,
+                    );
+
+                    file.set_installed(installed);
+
+                    files.push(file);
                 }
             }
 
@@ -1,14 +1,121 @@
-use std::{error::Error, process::Command};
+use std::{
+    collections::HashSet,
+    error::Error,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
+use conffiles::read_conffiles;
+use debmetadata::read_deb_metadata;
+use diversions::read_diversions;
 use dpkgquery::dpkg_query;
+use pathfilter::PathFilters;
 
-use super::LoadResult;
+use super::{alternatives::load_alternatives, LoadResult, Package, FILE_FLAG_CONFIG};
 
+mod conffiles;
+mod debmetadata;
+mod diversions;
 mod dpkgcsums;
 mod dpkgquery;
+mod pathfilter;
+mod statoverride;
 
-pub fn load_apt(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
-    let (packages, files) = dpkg_query(debug)?;
+/// dpkg alternatives administrative directory
+const ALTERNATIVES_DIR: &str = "/var/lib/dpkg/alternatives";
+
+/// apt package archive cache
+const ARCHIVES_DIR: &str = "/var/cache/apt/archives";
+
+pub fn load_apt(deb_metadata: bool, debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let (packages, mut files) = dpkg_query(debug)?;
+
+    // Fill in the file details dpkg doesn't record from the cached archives
+    if deb_metadata {
+        read_deb_metadata(&packages, &mut files, debug);
+    }
+
+    // Mark configuration files
+    let conffiles = read_conffiles(&packages);
+
+    if debug > 0 {
+        eprintln!("{} configuration files found", conffiles.len());
+    }
+
+    for file in &mut files {
+        if let Some(package) = file.package() {
+            if conffiles.contains(&(*package, file.path().to_path_buf())) {
+                file.set_flags(file.flags() | FILE_FLAG_CONFIG);
+            }
+        }
+    }
+
+    // Mark files excluded from installation by path-exclude
+    let filters = PathFilters::load(debug);
+
+    if !filters.is_empty() {
+        // dpkg doesn't record file types, so directories are identified by their contents
+        let dirs = files
+            .iter()
+            .filter_map(|file| file.path().parent())
+            .map(|dir| dir.to_path_buf())
+            .collect::<HashSet<_>>();
+
+        let mut excluded = 0;
+
+        for file in &mut files {
+            if filters.is_excluded(file.path(), dirs.contains(file.path())) {
+                file.exclude();
+                excluded += 1;
+            }
+        }
+
+        if debug > 0 {
+            eprintln!("{excluded} files excluded by dpkg path filters");
+        }
+    }
+
+    // Move diverted files to where they are actually installed
+    match read_diversions() {
+        Ok(diversions) => {
+            if debug > 0 {
+                eprintln!("{} diversions found", diversions.len());
+            }
+
+            for file in &mut files {
+                let Some(diversion) = diversions.get(file.path()) else {
+                    continue;
+                };
+
+                // The diverting package's own file is not diverted
+                let diverted = match (&diversion.package, file.package()) {
+                    (Some(diverter), Some(package)) => packages[*package].namestr() != *diverter,
+                    _ => true,
+                };
+
+                if diverted {
+                    if debug > 1 {
+                        eprintln!(
+                            "{} diverted to {}",
+                            file.path().display(),
+                            diversion.to.display()
+                        );
+                    }
+
+                    file.relocate(diversion.to.clone());
+                }
+            }
+        }
+        Err(e) => {
+            if debug > 0 {
+                eprintln!("Unable to read dpkg diversions ({e})");
+            }
+        }
+    }
+
+    // Add alternatives symbolic links
+    let alt_files = load_alternatives(Path::new(ALTERNATIVES_DIR), &files, debug);
+    files.extend(alt_files);
 
     // Default ignores for apt systems
     let ignores = vec![
@@ -25,3 +132,16 @@ pub fn apt_available() -> bool {
         _ => false,
     }
 }
+
+/// Finds the .deb for an installed package in the apt archive cache
+pub fn cached_deb(package: &Package) -> Option<PathBuf> {
+    // apt escapes the epoch separator in file names
+    let deb = Path::new(ARCHIVES_DIR).join(format!(
+        "{}_{}_{}.deb",
+        package.namestr(),
+        package.versionstr().replace(':', "%3a"),
+        package.archstr()
+    ));
+
+    deb.is_file().then_some(deb)
+}
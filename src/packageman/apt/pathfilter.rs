@@ -0,0 +1,112 @@
+use regex::Regex;
+use std::{fs, path::Path};
+
+use crate::packageman::glob_to_regex;
+
+/// Main dpkg configuration file
+const CONFIG_FILE: &str = "/etc/dpkg/dpkg.cfg";
+
+/// dpkg configuration fragment directory
+const CONFIG_DIR: &str = "/etc/dpkg/dpkg.cfg.d";
+
+/// A path-include or path-exclude filter
+struct Filter {
+    include: bool,
+    prefix: String,
+    regex: Regex,
+}
+
+/// dpkg path-include and path-exclude filters in the order they are configured
+pub struct PathFilters {
+    filters: Vec<Filter>,
+}
+
+impl PathFilters {
+    /// Reads the filters from the dpkg configuration
+    pub fn load(debug: u8) -> Self {
+        let mut config_files = vec![Path::new(CONFIG_FILE).to_path_buf()];
+
+        if let Ok(dir) = fs::read_dir(CONFIG_DIR) {
+            // dpkg only reads fragments with names made up of alphanumerics, '-' and '_'
+            let mut fragments = dir
+                .filter_map(|ent| ent.ok())
+                .filter(|ent| {
+                    ent.file_name().to_str().is_some_and(|name| {
+                        name.chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                    })
+                })
+                .map(|ent| ent.path())
+                .collect::<Vec<_>>();
+
+            fragments.sort();
+
+            config_files.extend(fragments);
+        }
+
+        let mut filters = Vec::new();
+
+        for config_file in config_files {
+            let Ok(contents) = fs::read_to_string(&config_file) else {
+                continue;
+            };
+
+            for line in contents.lines() {
+                let line = line.trim();
+                let line = line.strip_prefix("--").unwrap_or(line);
+
+                let Some((option, glob)) = line.split_once(['=', ' ', '\t']) else {
+                    continue;
+                };
+
+                let include = match option {
+                    "path-include" => true,
+                    "path-exclude" => false,
+                    _ => continue,
+                };
+
+                let glob = glob.trim();
+
+                if debug > 1 {
+                    eprintln!("dpkg {option} {glob} ({})", config_file.display());
+                }
+
+                match Regex::new(&glob_to_regex(glob)) {
+                    Ok(regex) => filters.push(Filter {
+                        include,
+                        prefix: glob[..glob.find(['*', '?', '[', '\\']).unwrap_or(glob.len())]
+                            .to_string(),
+                        regex,
+                    }),
+                    Err(e) => eprintln!("ERROR: Failed to convert dpkg filter '{glob}' ({e})"),
+                }
+            }
+        }
+
+        Self { filters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Returns true if dpkg would not have installed the path. As in dpkg the last matching
+    /// filter wins, and excluded directories which could lead to an included path are kept
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let path = path.to_string_lossy();
+
+        let excluded = self
+            .filters
+            .iter()
+            .rev()
+            .find(|filter| filter.regex.is_match(&path))
+            .is_some_and(|filter| !filter.include);
+
+        excluded
+            && !(is_dir
+                && self
+                    .filters
+                    .iter()
+                    .any(|filter| filter.include && path.starts_with(&filter.prefix)))
+    }
+}
@@ -0,0 +1,58 @@
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+use crate::packageman::ids::{group_id, user_id};
+
+/// dpkg database of owner and mode overrides
+const STATOVERRIDE_FILE: &str = "/var/lib/dpkg/statoverride";
+
+/// An owner and mode set by dpkg-statoverride
+pub struct StatOverride {
+    pub owner: (u32, u32),
+    /// Permission bits
+    pub mode: u32,
+}
+
+/// Reads the dpkg statoverride database, keyed by path. Each line is the user, group, octal
+/// mode and path. Users and groups are names, or IDs prefixed with '#'
+pub fn read_statoverrides() -> Result<HashMap<PathBuf, StatOverride>, Box<dyn Error>> {
+    let contents = fs::read_to_string(STATOVERRIDE_FILE)?;
+
+    let mut overrides = HashMap::new();
+
+    for line in contents.lines() {
+        let mut fields = line.splitn(4, ' ');
+
+        let (Some(user), Some(group), Some(mode), Some(path)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+
+        let (Some(uid), Some(gid), Ok(mode)) = (
+            resolve_id(user, user_id),
+            resolve_id(group, group_id),
+            u32::from_str_radix(mode, 8),
+        ) else {
+            eprintln!("ERROR: Invalid dpkg statoverride '{line}'");
+            continue;
+        };
+
+        overrides.insert(
+            PathBuf::from(path),
+            StatOverride {
+                owner: (uid, gid),
+                mode,
+            },
+        );
+    }
+
+    Ok(overrides)
+}
+
+/// Converts a '#' prefixed ID or a name to an ID
+fn resolve_id(id: &str, lookup: fn(&str) -> Option<u32>) -> Option<u32> {
+    match id.strip_prefix('#') {
+        Some(id) => id.parse().ok(),
+        None => lookup(id),
+    }
+}
@@ -0,0 +1,94 @@
+use std::{error::Error, fs, path::Path};
+
+use serde_json::Value;
+
+/// A crate installed by cargo install
+pub struct InstalledCrate {
+    pub name: String,
+    pub version: String,
+    pub bins: Vec<String>,
+}
+
+/// Reads .crates2.json. Installs are keyed by "<name> <version> (<source>)"
+/// and have a bins array
+pub fn read_crates2_json(path: &Path) -> Result<Vec<InstalledCrate>, Box<dyn Error>> {
+    let contents = fs::read(path)?;
+    let json: Value = serde_json::from_slice(&contents)?;
+
+    let installs = json["installs"]
+        .as_object()
+        .ok_or("No installs in .crates2.json")?;
+
+    let crates = installs
+        .iter()
+        .filter_map(|(key, details)| {
+            let bins = details["bins"]
+                .as_array()
+                .map(|bins| {
+                    bins.iter()
+                        .filter_map(|bin| bin.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            installed_crate(key, bins)
+        })
+        .collect();
+
+    Ok(crates)
+}
+
+/// Reads the older .crates.toml. Installs are listed in the v1 table as
+/// "<name> <version> (<source>)" = ["bin", ...]
+pub fn read_crates_toml(path: &Path) -> Result<Vec<InstalledCrate>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    let mut in_v1 = false;
+    let mut crates = Vec::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_v1 = line == "[v1]";
+            continue;
+        }
+
+        if !in_v1 {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim().trim_matches('"');
+
+        let bins = value
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|bin| bin.trim().trim_matches('"'))
+            .filter(|bin| !bin.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if let Some(installed) = installed_crate(key, bins) {
+            crates.push(installed);
+        }
+    }
+
+    Ok(crates)
+}
+
+fn installed_crate(key: &str, bins: Vec<String>) -> Option<InstalledCrate> {
+    let mut split = key.split(' ');
+
+    let name = split.next()?.to_string();
+    let version = split.next()?.to_string();
+
+    Some(InstalledCrate {
+        name,
+        version,
+        bins,
+    })
+}
@@ -0,0 +1,107 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use crates::{read_crates2_json, read_crates_toml, InstalledCrate};
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod crates;
+
+/// Install roots used with cargo install --root
+const INSTALL_ROOTS: [&str; 2] = ["/usr/local", "/usr"];
+
+pub fn load_cargo(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for root in install_roots() {
+        if debug > 0 {
+            eprintln!("Reading cargo install metadata in {}", root.display());
+        }
+
+        // Prefer the newer JSON metadata
+        let crates2_json = root.join(".crates2.json");
+        let crates_toml = root.join(".crates.toml");
+
+        let (metadata, result) = if crates2_json.exists() {
+            (&crates2_json, read_crates2_json(&crates2_json))
+        } else {
+            (&crates_toml, read_crates_toml(&crates_toml))
+        };
+
+        let installed = match result {
+            Ok(installed) => installed,
+            Err(e) => {
+                eprintln!("ERROR: Failed to read {} ({e})", metadata.display());
+                continue;
+            }
+        };
+
+        // The metadata files are shared by all crates
+        for metadata in [crates2_json, crates_toml] {
+            if metadata.exists() {
+                files.push(PackageFile::new(metadata, None, None, None, None, None));
+            }
+        }
+
+        for installed_crate in installed {
+            let package_elem = packages.len();
+
+            if debug > 1 {
+                eprintln!(
+                    "{} binaries found for {}",
+                    installed_crate.bins.len(),
+                    installed_crate.name
+                );
+            }
+
+            for bin in &installed_crate.bins {
+                files.push(PackageFile::new(
+                    root.join("bin").join(bin),
+                    Some(package_elem),
+                    None,
+                    None,
+                    None,
+                    None,
+                ));
+            }
+
+            packages.push(cargo_package(installed_crate));
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} cargo installed crates found", packages.len());
+    }
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn cargo_available() -> bool {
+    !install_roots().is_empty()
+}
+
+fn install_roots() -> Vec<PathBuf> {
+    INSTALL_ROOTS
+        .iter()
+        .map(Path::new)
+        .filter(|root| root.join(".crates2.json").exists() || root.join(".crates.toml").exists())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+fn cargo_package(installed_crate: InstalledCrate) -> Package {
+    Package::new(
+        OsString::from(format!(
+            "{}-{}",
+            installed_crate.name, installed_crate.version
+        )),
+        OsString::from(installed_crate.name),
+        OsString::from(installed_crate.version),
+        None,
+        Origin::Source(Source::Cargo),
+    )
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use packlist::packlist_files;
+use perllocal::perllocal_versions;
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod packlist;
+mod perllocal;
+
+pub fn load_cpan(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    let dirs = perl_dirs()?;
+
+    // Get module versions from the install logs. MakeMaker writes the log to the architecture
+    // library, but some distributions (eg. Debian) move it to the site library
+    let mut versions = HashMap::new();
+
+    for dir in dirs.arch.iter().chain(&dirs.site) {
+        versions.extend(perllocal_versions(dir, debug));
+    }
+
+    for site_dir in dirs.site {
+        let auto_dir = site_dir.join("auto");
+
+        if !auto_dir.is_dir() {
+            continue;
+        }
+
+        if debug > 0 {
+            eprintln!("Scanning Perl .packlist files in {}", auto_dir.display());
+        }
+
+        // Find .packlist files
+        let mut packlists = Vec::new();
+        find_packlists(&auto_dir, &mut packlists);
+        packlists.sort();
+
+        for packlist in packlists {
+            // Build module name from the path under auto (eg. auto/Foo/Bar/.packlist -> Foo::Bar)
+            let Some(module) = packlist
+                .parent()
+                .and_then(|dir| dir.strip_prefix(&auto_dir).ok())
+                .map(|dir| {
+                    dir.iter()
+                        .map(|part| part.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("::")
+                })
+            else {
+                continue;
+            };
+
+            match packlist_files(&packlist, packages.len()) {
+                Ok(package_files) => {
+                    let package = cpan_package(&module, &versions);
+
+                    if debug > 1 {
+                        eprintln!(
+                            "{} files found in {}",
+                            package_files.len(),
+                            package.name_ver_arch()
+                        );
+                    }
+
+                    // The .packlist itself belongs to the module
+                    files.push(PackageFile::new(
+                        packlist.clone(),
+                        Some(packages.len()),
+                        None,
+                        None,
+                        None,
+                        None,
+                    ));
+
+                    packages.push(package);
+                    files.extend(package_files);
+                }
+                Err(e) => eprintln!("ERROR: Failed to read {} ({e})", packlist.display()),
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} Perl modules found", packages.len());
+    }
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn cpan_available() -> bool {
+    match perl_dirs() {
+        Ok(dirs) => dirs.site.iter().any(|dir| dir.join("auto").is_dir()),
+        _ => false,
+    }
+}
+
+/// Perl library directories from the Perl configuration
+struct PerlDirs {
+    /// Site library directories, where cpan installs modules
+    site: Vec<PathBuf>,
+    /// Architecture library directory, where MakeMaker writes perllocal.pod
+    arch: Option<PathBuf>,
+}
+
+/// Gets the site and architecture library directories from the Perl configuration
+fn perl_dirs() -> Result<PerlDirs, Box<dyn Error>> {
+    let output = Command::new("perl")
+        .arg("-V:installsitearch")
+        .arg("-V:installsitelib")
+        .arg("-V:installarchlib")
+        .output()?;
+
+    // Successful?
+    if !output.status.success() {
+        Err(format!("perl returned {}", output.status))?
+    }
+
+    let config = String::from_utf8_lossy(&output.stdout);
+
+    // Each value is printed as name='value';
+    let value = |name: &str| {
+        config
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix(name)?
+                    .strip_prefix("='")?
+                    .strip_suffix("';")
+            })
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let mut site = [value("installsitearch"), value("installsitelib")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    site.dedup();
+
+    Ok(PerlDirs {
+        site,
+        arch: value("installarchlib"),
+    })
+}
+
+/// Recursively finds .packlist files
+fn find_packlists(dir: &Path, packlists: &mut Vec<PathBuf>) {
+    let Ok(ents) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for ent in ents.filter_map(|ent| ent.ok()) {
+        let path = ent.path();
+
+        if ent.file_name() == ".packlist" {
+            packlists.push(path);
+        } else if ent.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            find_packlists(&path, packlists);
+        }
+    }
+}
+
+fn cpan_package(module: &str, versions: &HashMap<String, String>) -> Package {
+    let version = versions.get(module).cloned().unwrap_or_default();
+
+    let fullname = if version.is_empty() {
+        module.to_string()
+    } else {
+        format!("{module}-{version}")
+    };
+
+    Package::new(
+        OsString::from(fullname),
+        OsString::from(module),
+        OsString::from(version),
+        None,
+        Origin::Source(Source::Cpan),
+    )
+}
@@ -0,0 +1,34 @@
+use std::{error::Error, fs, path::Path};
+
+use crate::packageman::PackageFile;
+
+/// Reads a .packlist file. Each line is an absolute path optionally followed by
+/// space separated key=value attributes (eg. type=link from=/path)
+pub fn packlist_files(
+    packlist: &Path,
+    package_elem: usize,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let contents = fs::read_to_string(packlist)?;
+
+    let files = contents
+        .lines()
+        .map(strip_attributes)
+        .filter(|path| path.starts_with('/'))
+        .map(|path| PackageFile::new(path.into(), Some(package_elem), None, None, None, None))
+        .collect();
+
+    Ok(files)
+}
+
+/// Removes trailing key=value attributes from a .packlist line
+fn strip_attributes(mut line: &str) -> &str {
+    while let Some((path, attribute)) = line.rsplit_once(' ') {
+        if !attribute.contains('=') {
+            break;
+        }
+
+        line = path;
+    }
+
+    line
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, fs, path::Path};
+
+/// Reads module versions from perllocal.pod, the install log written by ExtUtils::MakeMaker.
+/// Each install is recorded as:
+///
+/// ```text
+/// =head2 <date>: C<Module> L<Foo::Bar|Foo::Bar>
+/// ...
+/// C<VERSION: 1.23>
+/// ```
+///
+/// Later installs of a module override earlier ones
+pub fn perllocal_versions(dir: &Path, debug: u8) -> HashMap<String, String> {
+    let mut versions = HashMap::new();
+
+    let perllocal = dir.join("perllocal.pod");
+
+    let contents = match fs::read_to_string(&perllocal) {
+        Ok(contents) => contents,
+        Err(e) => {
+            if debug > 1 {
+                eprintln!("No Perl install log {} ({e})", perllocal.display());
+            }
+
+            return versions;
+        }
+    };
+
+    let mut module = None;
+
+    for line in contents.lines() {
+        if line.starts_with("=head2") {
+            // Extract module name from L<Name|Name>
+            module = line
+                .split_once("L<")
+                .and_then(|(_, rest)| rest.split(['|', '>']).next())
+                .map(str::to_string);
+        } else if let Some(version) = line
+            .trim()
+            .strip_prefix("C<VERSION: ")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            if let Some(module) = &module {
+                versions.insert(module.clone(), version.to_string());
+            }
+        }
+    }
+
+    versions
+}
@@ -0,0 +1,44 @@
+use std::{error::Error, fs, path::Path, process::Command};
+
+use protocol::parse_stream;
+pub use protocol::write_stream;
+
+use super::LoadResult;
+
+mod protocol;
+
+pub fn load_external_cmd(cmd: &str, debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    if debug > 0 {
+        eprintln!("Running external command '{cmd}'");
+    }
+
+    // Run the command via the shell so arguments can be passed
+    let output = Command::new("sh").arg("-c").arg(cmd).output()?;
+
+    // Successful?
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eprintln!("{}", stderr);
+        Err(format!(
+            "external command '{cmd}' returned {}",
+            output.status
+        ))?
+    }
+
+    let (packages, files) = parse_stream(&output.stdout, cmd, debug)?;
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn load_external_file(file: &Path, debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    if debug > 0 {
+        eprintln!("Reading external manifest {}", file.display());
+    }
+
+    let contents = fs::read(file)
+        .map_err(|e| format!("Failed to read external manifest {} ({e})", file.display()))?;
+
+    let (packages, files) = parse_stream(&contents, &file.to_string_lossy(), debug)?;
+
+    Ok((packages, files, Vec::new()))
+}
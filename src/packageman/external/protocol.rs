@@ -0,0 +1,203 @@
+use std::{error::Error, ffi::OsString, io::Write, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::packageman::{decode_hex, encode_hex, Origin, Package, PackageFile};
+
+/// A single record in an external package stream.
+///
+/// The stream is a sequence of JSON objects, normally one per line (NDJSON).
+/// Each `package` record is followed by the `file` records belonging to it:
+///
+/// ```text
+/// {"type":"package","name":"tool","version":"1.2","arch":"x86_64"}
+/// {"type":"file","path":"/opt/tool/bin/tool","size":1234,"mode":"100755","digest":"<sha256 hex>","mtime":1700000000}
+/// ```
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum Record {
+    Package {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        arch: Option<String>,
+    },
+    File {
+        path: PathBuf,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mode: Option<Mode>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        digest: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mtime: Option<i64>,
+    },
+}
+
+/// File mode, either numeric or an octal string
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum Mode {
+    Numeric(u32),
+    Octal(String),
+}
+
+/// Lengths of MD5 and SHA256 digests in hex
+const DIGEST_HEX_LENS: [usize; 2] = [32, 64];
+
+pub fn parse_stream(
+    stream: &[u8],
+    source: &str,
+    debug: u8,
+) -> Result<(Vec<Package>, Vec<PackageFile>), Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    let mut records = serde_json::Deserializer::from_slice(stream).into_iter::<Record>();
+
+    // Line the current record ends on, counted on from the end of the previous record
+    let mut line = 1;
+    let mut offset = 0;
+
+    while let Some(record) = records.next() {
+        let record = record.map_err(|e| format!("Failed to parse output of {source}: {e}"))?;
+
+        let end = records.byte_offset();
+        line += stream[offset..end].iter().filter(|c| **c == b'\n').count();
+        offset = end;
+
+        match record {
+            Record::Package {
+                name,
+                version,
+                arch,
+            } => {
+                let version = version.unwrap_or_default();
+
+                // Build full name
+                let fullname = if version.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{name}-{version}")
+                };
+
+                if debug > 1 {
+                    eprintln!("External package {fullname}");
+                }
+
+                packages.push(Package::new(
+                    OsString::from(fullname),
+                    OsString::from(name),
+                    OsString::from(version),
+                    arch.map(OsString::from),
+                    Origin::External,
+                ));
+            }
+            Record::File {
+                path,
+                size,
+                mode,
+                digest,
+                mtime,
+            } => {
+                if packages.is_empty() {
+                    Err(format!(
+                        "File {} precedes any package in {source}",
+                        path.display()
+                    ))?
+                }
+
+                if !path.is_absolute() {
+                    Err(format!(
+                        "File path {} is not absolute in {source}",
+                        path.display()
+                    ))?
+                }
+
+                let mode = match mode {
+                    Some(Mode::Numeric(mode)) => Some(mode),
+                    Some(Mode::Octal(mode)) => {
+                        Some(u32::from_str_radix(&mode, 8).map_err(|e| {
+                            format!("Failed to parse file mode '{mode}' in {source}: {e}")
+                        })?)
+                    }
+                    None => None,
+                };
+
+                // Only MD5 and SHA256 digests can be verified
+                let chksum = match digest {
+                    Some(digest)
+                        if !DIGEST_HEX_LENS.contains(&digest.len()) || !digest.is_ascii() =>
+                    {
+                        Err(format!(
+                            "Digest '{digest}' for {} in {source} line {line} is not MD5 or SHA256",
+                            path.display()
+                        ))?
+                    }
+                    Some(digest) => Some(decode_hex(&digest).map_err(|e| {
+                        format!("Failed to parse digest '{digest}' in {source} line {line}: {e}")
+                    })?),
+                    None => None,
+                };
+
+                files.push(PackageFile::new(
+                    path,
+                    Some(packages.len() - 1),
+                    size,
+                    mode,
+                    chksum,
+                    mtime,
+                ));
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!(
+            "{} packages and {} files found in {source}",
+            packages.len(),
+            files.len()
+        );
+    }
+
+    Ok((packages, files))
+}
+
+pub fn write_stream(
+    packages: &[Package],
+    files: &[PackageFile],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+    for (package_elem, package) in packages.iter().enumerate() {
+        let record = Record::Package {
+            name: package.namestr().into_owned(),
+            version: non_empty(package.versionstr().into_owned()),
+            arch: non_empty(package.archstr().into_owned()),
+        };
+
+        serde_json::to_writer(&mut *out, &record)?;
+        writeln!(out)?;
+
+        for file in files
+            .iter()
+            .filter(|file| *file.package() == Some(package_elem))
+        {
+            let record = Record::File {
+                path: file.path().to_path_buf(),
+                size: *file.size(),
+                mode: file.mode().map(|mode| Mode::Octal(format!("{:o}", mode))),
+                digest: file.chksum().as_ref().map(|chksum| encode_hex(chksum)),
+                mtime: *file.time(),
+            };
+
+            serde_json::to_writer(&mut *out, &record)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+/// System wide flatpak installation
+pub const FLATPAK_DIR: &str = "/var/lib/flatpak";
+
+/// Kinds of deployment in the installation
+const KINDS: [&str; 2] = ["app", "runtime"];
+
+pub fn load_flatpak(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    if debug > 0 {
+        eprintln!("Scanning flatpaks in {}", FLATPAK_DIR);
+    }
+
+    for kind in KINDS {
+        // Deployments are laid out as <kind>/<id>/<arch>/<branch>
+        for id_dir in sorted_dirs(&Path::new(FLATPAK_DIR).join(kind)) {
+            let package_elem = packages.len();
+
+            let id = file_name(&id_dir);
+
+            let mut arch = None;
+            let mut branches = Vec::new();
+
+            for arch_dir in sorted_dirs(&id_dir) {
+                for branch_dir in sorted_dirs(&arch_dir) {
+                    // Only count branches with an active deployment
+                    if branch_dir.join("active").exists() {
+                        branches.push(file_name(&branch_dir));
+                        arch = Some(file_name(&arch_dir));
+                    }
+                }
+            }
+
+            if branches.is_empty() {
+                if debug > 1 {
+                    eprintln!("No active deployment for flatpak {id}");
+                }
+
+                continue;
+            }
+
+            let version = branches.join(",");
+
+            if debug > 1 {
+                eprintln!("Flatpak {kind} {id} branch {version}");
+            }
+
+            // The deployment directory is owned in its entirety
+            files.push(PackageFile::new_tree(id_dir.clone(), Some(package_elem)));
+
+            packages.push(Package::new(
+                OsString::from(format!("{id}-{version}")),
+                OsString::from(id),
+                OsString::from(version),
+                arch.map(OsString::from),
+                Origin::Source(Source::Flatpak),
+            ));
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} flatpaks found", packages.len());
+    }
+
+    // The repository and exported files are managed by flatpak
+    let ignores =
+        vec!["^/var/lib/flatpak/(repo|appstream|exports|\\.changed|\\.removed)($|/.*)".into()];
+
+    Ok((packages, files, ignores))
+}
+
+pub fn flatpak_available() -> bool {
+    KINDS
+        .iter()
+        .any(|kind| Path::new(FLATPAK_DIR).join(kind).is_dir())
+}
+
+fn sorted_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(ents) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut dirs = ents
+        .filter_map(|ent| ent.ok())
+        .filter(|ent| ent.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|ent| ent.path())
+        .collect::<Vec<_>>();
+
+    dirs.sort();
+
+    dirs
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,65 @@
+use std::{error::Error, fs, path::Path, sync::LazyLock};
+
+use regex::Regex;
+
+/// Details read from an installed gem specification
+pub struct GemSpec {
+    /// File stem (name-version[-platform]), used to name the gem's directories
+    pub stem: String,
+    pub name: String,
+    pub version: String,
+    pub platform: Option<String>,
+    pub executables: Vec<String>,
+}
+
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\.name\s*=\s*"([^"]+)""#).unwrap());
+static VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.version\s*=\s*"([^"]+)""#).unwrap());
+static PLATFORM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.platform\s*=\s*"([^"]+)""#).unwrap());
+static EXECUTABLES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.executables\s*=\s*\[([^\]]*)\]"#).unwrap());
+static STRING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]+)""#).unwrap());
+
+/// Reads the fields needed from a Ruby gem specification file, falling back to the
+/// file name for the name and version
+pub fn read_gemspec(path: &Path) -> Result<GemSpec, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    let stem = path
+        .file_stem()
+        .ok_or("No file name")?
+        .to_string_lossy()
+        .into_owned();
+
+    let capture = |regex: &Regex| {
+        regex
+            .captures(&contents)
+            .map(|captures| captures[1].to_string())
+    };
+
+    let (stem_name, stem_version) = match stem.rsplit_once('-') {
+        Some((name, version)) => (name, version),
+        None => (stem.as_str(), ""),
+    };
+
+    let name = capture(&NAME).unwrap_or_else(|| stem_name.to_string());
+    let version = capture(&VERSION).unwrap_or_else(|| stem_version.to_string());
+    let platform = capture(&PLATFORM).filter(|platform| platform != "ruby");
+
+    let executables = match EXECUTABLES.captures(&contents) {
+        Some(captures) => STRING
+            .captures_iter(&captures[1])
+            .map(|captures| captures[1].to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(GemSpec {
+        stem,
+        name,
+        version,
+        platform,
+        executables,
+    })
+}
@@ -0,0 +1,178 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gemspec::{read_gemspec, GemSpec};
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod gemspec;
+
+/// Directories containing gem directories, either directly or one per Ruby version
+const GEM_ROOTS: [&str; 4] = [
+    "/var/lib/gems",
+    "/usr/local/lib/ruby/gems",
+    "/usr/local/lib64/ruby/gems",
+    "/usr/local/share/gems",
+];
+
+/// Directory gem executables are installed in to
+const BIN_DIR: &str = "/usr/local/bin";
+
+pub fn load_gem(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for gem_dir in gem_dirs() {
+        if debug > 0 {
+            eprintln!("Scanning gems in {}", gem_dir.display());
+        }
+
+        let mut specs = fs::read_dir(gem_dir.join("specifications"))?
+            .filter_map(|ent| ent.ok())
+            .map(|ent| ent.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "gemspec"))
+            .collect::<Vec<_>>();
+
+        specs.sort();
+
+        for spec_path in specs {
+            match read_gemspec(&spec_path) {
+                Ok(spec) => {
+                    let package_elem = packages.len();
+                    let package = gem_package(&spec);
+
+                    let package_files = gem_files(&gem_dir, &spec_path, &spec, package_elem);
+
+                    if debug > 1 {
+                        eprintln!(
+                            "{} paths found for {}",
+                            package_files.len(),
+                            package.name_ver_arch()
+                        );
+                    }
+
+                    packages.push(package);
+                    files.extend(package_files);
+                }
+                Err(e) => eprintln!(
+                    "ERROR: Failed to read gem specification {} ({e})",
+                    spec_path.display()
+                ),
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} gems found", packages.len());
+    }
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn gem_available() -> bool {
+    !gem_dirs().is_empty()
+}
+
+/// Finds gem directories (those containing a specifications directory)
+fn gem_dirs() -> Vec<PathBuf> {
+    let is_gem_dir = |dir: &Path| dir.join("specifications").is_dir();
+
+    let mut dirs = Vec::new();
+
+    for root in GEM_ROOTS.iter().map(Path::new) {
+        if is_gem_dir(root) {
+            dirs.push(root.to_path_buf());
+        } else if let Ok(ents) = fs::read_dir(root) {
+            let mut versions = ents
+                .filter_map(|ent| ent.ok())
+                .map(|ent| ent.path())
+                .filter(|dir| is_gem_dir(dir))
+                .collect::<Vec<_>>();
+
+            versions.sort();
+
+            dirs.extend(versions);
+        }
+    }
+
+    dirs
+}
+
+/// Builds the list of paths owned by a gem
+fn gem_files(
+    gem_dir: &Path,
+    spec_path: &Path,
+    spec: &GemSpec,
+    package_elem: usize,
+) -> Vec<PackageFile> {
+    let mut files = Vec::new();
+
+    let file = |path: PathBuf| PackageFile::new(path, Some(package_elem), None, None, None, None);
+
+    // The specification and the installed gem directory
+    files.push(file(spec_path.to_path_buf()));
+    files.push(PackageFile::new_tree(
+        gem_dir.join("gems").join(&spec.stem),
+        Some(package_elem),
+    ));
+
+    // Optional cached gem, build information and documentation
+    for path in [
+        gem_dir.join("cache").join(format!("{}.gem", spec.stem)),
+        gem_dir
+            .join("build_info")
+            .join(format!("{}.info", spec.stem)),
+    ] {
+        if path.exists() {
+            files.push(file(path));
+        }
+    }
+
+    let doc_dir = gem_dir.join("doc").join(&spec.stem);
+
+    if doc_dir.is_dir() {
+        files.push(PackageFile::new_tree(doc_dir, Some(package_elem)));
+    }
+
+    // Compiled extensions (extensions/<platform>/<ruby abi>/<gem>)
+    if let Ok(platforms) = fs::read_dir(gem_dir.join("extensions")) {
+        for platform in platforms.filter_map(|ent| ent.ok()) {
+            if let Ok(abis) = fs::read_dir(platform.path()) {
+                for abi in abis.filter_map(|ent| ent.ok()) {
+                    let ext_dir = abi.path().join(&spec.stem);
+
+                    if ext_dir.is_dir() {
+                        files.push(PackageFile::new_tree(ext_dir, Some(package_elem)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Executable wrappers
+    for executable in &spec.executables {
+        for bin_dir in [gem_dir.join("bin"), PathBuf::from(BIN_DIR)] {
+            let path = bin_dir.join(executable);
+
+            if path.symlink_metadata().is_ok() {
+                files.push(file(path));
+            }
+        }
+    }
+
+    files
+}
+
+fn gem_package(spec: &GemSpec) -> Package {
+    Package::new(
+        OsString::from(&spec.stem),
+        OsString::from(&spec.name),
+        OsString::from(&spec.version),
+        spec.platform.as_ref().map(OsString::from),
+        Origin::Source(Source::Gem),
+    )
+}
@@ -0,0 +1,36 @@
+use std::{collections::HashMap, fs, sync::LazyLock};
+
+/// Local user names and IDs
+static USERS: LazyLock<HashMap<String, u32>> = LazyLock::new(|| read_ids("/etc/passwd"));
+
+/// Local group names and IDs
+static GROUPS: LazyLock<HashMap<String, u32>> = LazyLock::new(|| read_ids("/etc/group"));
+
+/// Looks up a local user ID by name
+pub fn user_id(name: &str) -> Option<u32> {
+    USERS.get(name).copied()
+}
+
+/// Looks up a local group ID by name
+pub fn group_id(name: &str) -> Option<u32> {
+    GROUPS.get(name).copied()
+}
+
+/// Reads the names and IDs (first and third fields) from /etc/passwd or /etc/group
+fn read_ids(file: &str) -> HashMap<String, u32> {
+    let Ok(contents) = fs::read_to_string(file) else {
+        return HashMap::new();
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+
+            Some((name.to_string(), id))
+        })
+        .collect()
+}
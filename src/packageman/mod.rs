@@ -1,70 +1,250 @@
 use clap::ValueEnum;
+use libc::{S_IFBLK, S_IFCHR, S_IFDIR, S_IFIFO, S_IFLNK, S_IFMT, S_IFREG, S_IFSOCK};
 pub use package::Package;
-pub use packagefile::PackageFile;
+pub use packagefile::{FileState, PackageFile, FILE_FLAG_CONFIG, FILE_FLAG_DOC};
 use std::{
-    collections::HashSet,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     error::Error,
-    fs::canonicalize,
+    fs,
     num::ParseIntError,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
-use apt::{apt_available, load_apt};
-use rpm::{load_rpm, rpm_available};
+use aliases::Aliases;
+use apt::{apt_available, cached_deb, load_apt};
+use cargo::{cargo_available, load_cargo};
+use cpan::{cpan_available, load_cpan};
+use external::{load_external_cmd, load_external_file};
+use flatpak::{flatpak_available, load_flatpak};
+use gem::{gem_available, load_gem};
+use nix::{load_nix, nix_available};
+use npm::{load_npm, npm_available};
+use opkg::{load_opkg, opkg_available};
+use pip::{load_pip, pip_available};
+use rpm::{cached_rpm, load_rpm, rpm_available};
+use slackware::{load_slackware, slackware_available};
+use snap::{load_snap, snap_available};
+use tarball::load_tarball;
+pub use tarball::{tarball_manifest, TarballSpec};
+use xbps::{cached_xbps, load_xbps, xbps_available};
 
+mod aliases;
+mod alternatives;
 mod apt;
+mod cargo;
+mod cpan;
+mod external;
+mod flatpak;
+mod gem;
+mod ids;
+mod nix;
+mod npm;
+mod opkg;
 mod package;
 mod packagefile;
+mod pip;
 mod rpm;
+mod slackware;
+mod snap;
+mod tarball;
+mod xbps;
 
-#[derive(ValueEnum, Clone)]
+/// Directories holding the read-only image mounts of the snap and flatpak sources
+pub const IMAGE_MOUNT_DIRS: [&str; 2] = [snap::MOUNT_DIR, flatpak::FLATPAK_DIR];
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PackageMgr {
     Rpm,
     Apt,
+    Opkg,
+    Xbps,
+    Slackware,
+}
+
+/// Secondary sources of file ownership
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Python packages installed by pip
+    Pip,
+    /// Node.js packages installed globally by npm
+    Npm,
+    /// Perl modules installed by cpan/cpanm
+    Cpan,
+    /// Ruby gems installed by gem install
+    Gem,
+    /// Rust binaries installed by cargo install
+    Cargo,
+    /// System wide flatpak applications and runtimes
+    Flatpak,
+    /// Snap packages
+    Snap,
+    /// Nix store paths
+    Nix,
+}
+
+impl Source {
+    fn available(&self) -> bool {
+        match self {
+            Source::Pip => pip_available(),
+            Source::Npm => npm_available(),
+            Source::Cpan => cpan_available(),
+            Source::Gem => gem_available(),
+            Source::Cargo => cargo_available(),
+            Source::Flatpak => flatpak_available(),
+            Source::Snap => snap_available(),
+            Source::Nix => nix_available(),
+        }
+    }
+
+    fn load(&self, debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+        match self {
+            Source::Pip => load_pip(debug),
+            Source::Npm => load_npm(debug),
+            Source::Cpan => load_cpan(debug),
+            Source::Gem => load_gem(debug),
+            Source::Cargo => load_cargo(debug),
+            Source::Flatpak => load_flatpak(debug),
+            Source::Snap => load_snap(debug),
+            Source::Nix => load_nix(debug),
+        }
+    }
+}
+
+/// Where a package was loaded from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// The primary package manager
+    Mgr(PackageMgr),
+    /// An external command or manifest file
+    External,
+    /// A tar archive
+    Tarball,
+    /// A secondary ownership source
+    Source(Source),
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Mgr(mgr) => match mgr.to_possible_value() {
+                Some(value) => f.write_str(value.get_name()),
+                None => f.write_str("unknown"),
+            },
+            Origin::External => f.write_str("external"),
+            Origin::Tarball => f.write_str("tarball"),
+            Origin::Source(source) => match source.to_possible_value() {
+                Some(value) => f.write_str(value.get_name()),
+                None => f.write_str("unknown"),
+            },
+        }
+    }
+}
+
+pub struct LoadArgs {
+    pub sources: Vec<Source>,
+    pub external_cmds: Vec<String>,
+    pub external_files: Vec<PathBuf>,
+    pub tarballs: Vec<TarballSpec>,
+    pub deb_metadata: bool,
+    pub debug: u8,
 }
 
 pub struct PackageDb {
     packages: Vec<Package>,
     files: Vec<PackageFile>,
-    cset: HashSet<PathBuf>,
-    ignores: Vec<String>,
+    aliases: Aliases,
+    cfiles: HashMap<PathBuf, Vec<usize>>,
+    link_targets: HashSet<PathBuf>,
+    trees: HashSet<PathBuf>,
+    duplicates: Vec<(usize, usize)>,
+    ignores: Vec<(Origin, String)>,
 }
 
 pub type LoadResult = (Vec<Package>, Vec<PackageFile>, Vec<String>);
 
+/// Load results combined from each source, with ignores tagged with where they came from
+type Merged = (Vec<Package>, Vec<PackageFile>, Vec<(Origin, String)>);
+
 impl PackageDb {
     pub fn detect_mgr() -> Result<PackageMgr, Box<dyn Error>> {
-        let rpm_available = rpm_available();
-        let apt_available = apt_available();
-
-        if rpm_available {
-            if apt_available {
-                Err("No package manager specified")?
-            }
+        let available = PackageMgr::value_variants()
+            .iter()
+            .filter(|mgr| match mgr {
+                PackageMgr::Rpm => rpm_available(),
+                PackageMgr::Apt => apt_available(),
+                PackageMgr::Opkg => opkg_available(),
+                PackageMgr::Xbps => xbps_available(),
+                PackageMgr::Slackware => slackware_available(),
+            })
+            .collect::<Vec<_>>();
 
-            Ok(PackageMgr::Rpm)
-        } else if apt_available {
-            Ok(PackageMgr::Apt)
-        } else {
-            Err("No supported package managers available")?
+        match available[..] {
+            [mgr] => Ok(*mgr),
+            [] => Err("No supported package managers available")?,
+            _ => Err("No package manager specified")?,
         }
     }
 
-    pub fn load(mgr: PackageMgr, debug: u8) -> Result<PackageDb, Box<dyn Error>> {
-        match mgr {
-            PackageMgr::Rpm => Self::load_rpm(debug),
-            PackageMgr::Apt => Self::load_apt(debug),
-        }
+    pub fn detect_sources() -> Vec<Source> {
+        Source::value_variants()
+            .iter()
+            .filter(|source| source.available())
+            .copied()
+            .collect()
     }
 
-    fn load_rpm(debug: u8) -> Result<PackageDb, Box<dyn Error>> {
-        let (packages, files, ignores) = load_rpm(debug)?;
+    pub fn load(mgr: PackageMgr, args: &LoadArgs) -> Result<PackageDb, Box<dyn Error>> {
+        let debug = args.debug;
 
-        Ok(Self::new(packages, files, ignores, debug))
-    }
+        // Load the primary package manager database
+        let mgr_result = match mgr {
+            PackageMgr::Rpm => load_rpm(debug)?,
+            PackageMgr::Apt => load_apt(args.deb_metadata, debug)?,
+            PackageMgr::Opkg => load_opkg(debug)?,
+            PackageMgr::Xbps => load_xbps(debug)?,
+            PackageMgr::Slackware => load_slackware(debug)?,
+        };
+
+        let mut result = Merged::default();
+        merge_result(&mut result, mgr_result, Origin::Mgr(mgr));
+
+        // Add secondary ownership sources
+        for source in &args.sources {
+            match source.load(debug) {
+                Ok(source_result) => {
+                    merge_result(&mut result, source_result, Origin::Source(*source))
+                }
+                Err(e) => eprintln!(
+                    "ERROR: Failed to load {} packages ({e})",
+                    Origin::Source(*source)
+                ),
+            }
+        }
+
+        // Add external sources
+        for cmd in &args.external_cmds {
+            merge_result(
+                &mut result,
+                load_external_cmd(cmd, debug)?,
+                Origin::External,
+            );
+        }
+
+        for file in &args.external_files {
+            merge_result(
+                &mut result,
+                load_external_file(file, debug)?,
+                Origin::External,
+            );
+        }
+
+        // Add tarballs
+        for tarball in &args.tarballs {
+            merge_result(&mut result, load_tarball(tarball, debug)?, Origin::Tarball);
+        }
 
-    fn load_apt(debug: u8) -> Result<PackageDb, Box<dyn Error>> {
-        let (packages, files, ignores) = load_apt(debug)?;
+        let (packages, files, ignores) = result;
 
         Ok(Self::new(packages, files, ignores, debug))
     }
@@ -72,7 +252,7 @@ impl PackageDb {
     fn new(
         packages: Vec<Package>,
         mut files: Vec<PackageFile>,
-        ignores: Vec<String>,
+        ignores: Vec<(Origin, String)>,
         debug: u8,
     ) -> PackageDb {
         // Sort file list
@@ -117,19 +297,76 @@ impl PackageDb {
             files.sort_by(|a, b| a.path().cmp(b.path()));
         }
 
-        // Build hashset of canonical names
-        let cset = files
+        // Build alias map of symbolically linked directories
+        let aliases = Aliases::load(debug);
+
+        // Build map of canonical names to file indexes
+        let canonical = |file: &PackageFile| aliases.resolve(file.path()).into_owned();
+
+        let mut cfiles: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+
+        for (elem, file) in files.iter().enumerate() {
+            cfiles.entry(canonical(file)).or_default().push(elem);
+        }
+
+        // Targets of packaged symbolic links are also treated as packaged. Without a packaged mode
+        // only files which may be links are checked on disk: dpkg records a checksum for every
+        // regular file, and a path with packaged files beneath it is a directory
+        let link_targets = files
             .iter()
-            .map(|file| match canonicalize(file.path()) {
-                Ok(path) => path,
-                Err(_) => PathBuf::from(file.path()),
+            .enumerate()
+            .filter(|(elem, file)| match file.mode() {
+                Some(mode) => mode & S_IFMT == S_IFLNK,
+                None => {
+                    file.chksum().is_none()
+                        && !files
+                            .get(elem + 1)
+                            .is_some_and(|next| next.path().starts_with(file.path()))
+                        && file.path().is_symlink()
+                }
             })
+            .filter_map(|(_, file)| fs::canonicalize(file.path()).ok())
             .collect::<HashSet<_>>();
 
+        // Build hashset of canonical names of directories owned in their entirety
+        let trees = files
+            .iter()
+            .filter(|file| file.is_tree())
+            .map(canonical)
+            .collect::<HashSet<_>>();
+
+        // Find packaged paths which are the same as another packaged path via an alias
+        let direct = files
+            .iter()
+            .enumerate()
+            .map(|(elem, file)| (file.path(), elem))
+            .collect::<HashMap<_, _>>();
+
+        let duplicates = files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.package().is_some() && !aliases.is_alias(file.path()))
+            .filter_map(|(elem, file)| match aliases.resolve(file.path()) {
+                Cow::Owned(resolved) => direct
+                    .get(resolved.as_path())
+                    .filter(|_| !resolved.is_dir())
+                    .map(|other| (elem, *other)),
+                Cow::Borrowed(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        if debug > 0 && !duplicates.is_empty() {
+            eprintln!("{} aliased duplicate paths found", duplicates.len());
+        }
+
         PackageDb {
             packages,
             files,
-            cset,
+            aliases,
+            cfiles,
+            link_targets,
+            trees,
+            duplicates,
             ignores,
         }
     }
@@ -138,11 +375,16 @@ impl PackageDb {
         self.packages.iter()
     }
 
+    pub fn package(&self, idx: usize) -> &Package {
+        &self.packages[idx]
+    }
+
     pub fn files(&self) -> impl Iterator<Item = &PackageFile> {
         self.files.iter()
     }
 
-    pub fn ignores(&self) -> impl Iterator<Item = &String> {
+    /// Default ignore regular expressions and the source defining them
+    pub fn ignores(&self) -> impl Iterator<Item = &(Origin, String)> {
         self.ignores.iter()
     }
 
@@ -159,9 +401,149 @@ impl PackageDb {
         }
     }
 
+    /// Packaged files which are the same file as another packaged file via a directory alias
+    pub fn aliased_duplicates(&self) -> impl Iterator<Item = (&PackageFile, &PackageFile)> {
+        self.duplicates
+            .iter()
+            .map(|(aliased, other)| (&self.files[*aliased], &self.files[*other]))
+    }
+
+    /// Resolves directory aliases in a path, giving the form used by find_canonical
+    pub fn canonical<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        self.aliases.resolve(path)
+    }
+
+    /// Finds the package manager's cached archive of an installed package
+    pub fn cached_archive(&self, package: &Package) -> Option<PathBuf> {
+        match package.origin() {
+            Origin::Mgr(PackageMgr::Apt) => cached_deb(package),
+            Origin::Mgr(PackageMgr::Rpm) => cached_rpm(package),
+            Origin::Mgr(PackageMgr::Xbps) => cached_xbps(package),
+            _ => None,
+        }
+    }
+
+    /// Packaged files which are the path once directory aliases are resolved
+    pub fn find_files(&self, path: &Path) -> Vec<&PackageFile> {
+        self.cfiles
+            .get(self.canonical(path).as_ref())
+            .map(|elems| elems.iter().map(|elem| &self.files[*elem]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Finds the nearest directory owned in its entirety which contains the canonical path
+    pub fn find_containing_tree(&self, path: &Path) -> Option<&PackageFile> {
+        let tree = path
+            .ancestors()
+            .skip(1)
+            .find(|anc| self.find_canonical_tree(anc))?;
+
+        self.find_files(tree)
+            .into_iter()
+            .find(|file| file.is_tree())
+    }
+
     pub fn find_canonical(&self, path: &Path) -> bool {
-        self.cset.contains(path)
+        self.cfiles.contains_key(path) || self.link_targets.contains(path)
+    }
+
+    pub fn find_canonical_tree(&self, path: &Path) -> bool {
+        self.trees.contains(path)
+    }
+}
+
+/// Appends a load result to the combined results, renumbering the package indexes
+fn merge_result(into: &mut Merged, from: LoadResult, origin: Origin) {
+    let (packages, mut files, ignores) = from;
+
+    let offset = into.0.len();
+
+    for file in &mut files {
+        file.rebase_package(offset);
     }
+
+    into.0.extend(packages);
+    into.1.extend(files);
+    into.2
+        .extend(ignores.into_iter().map(|ignore| (origin, ignore)));
+}
+
+/// Lexically removes . and .. components from a path without touching the filesystem
+pub fn normalise_path(path: &Path) -> PathBuf {
+    let mut normalised = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalised.pop();
+            }
+            component => normalised.push(component),
+        }
+    }
+
+    normalised
+}
+
+/// Converts a shell glob (as used by fnmatch without flags) in to an anchored regular expression
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => match chars.next() {
+                Some(c) => regex.push_str(&regex::escape(&c.to_string())),
+                None => regex.push_str("\\\\"),
+            },
+            '[' => {
+                let class = chars.clone().position(|c| c == ']');
+
+                match class {
+                    Some(len) if len > 0 => {
+                        regex.push('[');
+
+                        for (i, c) in chars.by_ref().take(len + 1).enumerate() {
+                            match c {
+                                '!' | '^' if i == 0 => regex.push('^'),
+                                '\\' | '[' => {
+                                    regex.push('\\');
+                                    regex.push(c);
+                                }
+                                c => regex.push(c),
+                            }
+                        }
+                    }
+                    _ => regex.push_str("\\["),
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+
+    regex
+}
+
+/// Describes the file type of a mode
+pub fn file_type_name(mode: u32) -> &'static str {
+    match mode & S_IFMT {
+        S_IFREG => "regular file",
+        S_IFDIR => "directory",
+        S_IFLNK => "symbolic link",
+        S_IFCHR => "character device",
+        S_IFBLK => "block device",
+        S_IFIFO => "fifo",
+        S_IFSOCK => "socket",
+        _ => "unknown type",
+    }
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 pub fn decode_hex(s: &str) -> Result<Vec<u8>, ParseIntError> {
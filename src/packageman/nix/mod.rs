@@ -0,0 +1,81 @@
+use std::{error::Error, ffi::OsString, path::Path};
+
+use nixdb::read_valid_paths;
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod nixdb;
+mod nixhash;
+
+/// Nix store database
+const DB_FILE: &str = "/nix/var/nix/db/db.sqlite";
+
+pub fn load_nix(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    if debug > 0 {
+        eprintln!("Reading Nix store database {}", DB_FILE);
+    }
+
+    let valid_paths = read_valid_paths(Path::new(DB_FILE), debug)?;
+
+    let mut packages = Vec::with_capacity(valid_paths.len());
+    let mut files = Vec::with_capacity(valid_paths.len());
+
+    for valid_path in valid_paths {
+        let package_elem = packages.len();
+
+        // Store paths are named /nix/store/<hash>-<name>
+        let Some(fullname) = valid_path
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+
+        let name_ver = fullname
+            .split_once('-')
+            .map(|(_, name_ver)| name_ver)
+            .unwrap_or(&fullname);
+
+        // Split the version at the first hyphen followed by a digit
+        let (name, version) = match name_ver
+            .match_indices('-')
+            .find(|(pos, _)| name_ver[pos + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            Some((pos, _)) => (&name_ver[..pos], &name_ver[pos + 1..]),
+            None => (name_ver, ""),
+        };
+
+        packages.push(Package::new(
+            OsString::from(&fullname),
+            OsString::from(name),
+            OsString::from(version),
+            None,
+            Origin::Source(Source::Nix),
+        ));
+
+        // Each store path is owned in its entirety
+        files.push(PackageFile::new_tree_with_digest(
+            valid_path.path,
+            Some(package_elem),
+            valid_path.nar_size,
+            valid_path.nar_hash,
+        ));
+    }
+
+    if debug > 0 {
+        eprintln!("{} Nix store paths found", packages.len());
+    }
+
+    // Nix state and the hard link optimisation directory
+    let ignores = vec![
+        "^/nix/var($|/.*)".into(),
+        "^/nix/store/\\.links($|/.*)".into(),
+    ];
+
+    Ok((packages, files, ignores))
+}
+
+pub fn nix_available() -> bool {
+    Path::new(DB_FILE).exists()
+}
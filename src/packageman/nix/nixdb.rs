@@ -0,0 +1,51 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{Connection, OpenFlags};
+
+use super::nixhash::parse_nix_hash;
+
+/// A registered store path
+pub struct ValidPath {
+    pub path: PathBuf,
+    pub nar_hash: Option<Vec<u8>>,
+    pub nar_size: Option<usize>,
+}
+
+/// Reads the registered store paths from the ValidPaths table
+pub fn read_valid_paths(db_file: &Path, debug: u8) -> Result<Vec<ValidPath>, Box<dyn Error>> {
+    let conn = Connection::open_with_flags(db_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open {} ({e})", db_file.display()))?;
+
+    let mut stmt = conn.prepare("SELECT path, hash, narSize FROM ValidPaths ORDER BY path")?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+        ))
+    })?;
+
+    let mut valid_paths = Vec::new();
+
+    for row in rows {
+        let (path, hash, nar_size) = row?;
+
+        let nar_hash = parse_nix_hash(&hash);
+
+        if debug > 2 && nar_hash.is_none() {
+            eprintln!("Unrecognised NAR hash '{hash}' for {path}");
+        }
+
+        valid_paths.push(ValidPath {
+            path: PathBuf::from(path),
+            nar_hash,
+            nar_size: nar_size.and_then(|size| usize::try_from(size).ok()),
+        });
+    }
+
+    Ok(valid_paths)
+}
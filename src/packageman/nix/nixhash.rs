@@ -0,0 +1,49 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::packageman::decode_hex;
+
+/// Nix's base32 alphabet (omits e, o, u and t)
+const BASE32_CHARS: &[u8] = b"0123456789abcdfghijklmnpqrsvwxyz";
+
+const SHA256_LEN: usize = 32;
+
+/// Parses a SHA256 hash in any of the forms Nix writes:
+/// sha256:<base16>, sha256:<nix base32> or sha256-<base64> (SRI)
+pub fn parse_nix_hash(hash: &str) -> Option<Vec<u8>> {
+    if let Some(sri) = hash.strip_prefix("sha256-") {
+        return STANDARD.decode(sri).ok().filter(|h| h.len() == SHA256_LEN);
+    }
+
+    let hash = hash.strip_prefix("sha256:")?;
+
+    match hash.len() {
+        64 if hash.is_ascii() => decode_hex(hash).ok(),
+        52 => decode_base32(hash),
+        _ => None,
+    }
+}
+
+/// Decodes Nix base32, which is written least significant digit last
+fn decode_base32(s: &str) -> Option<Vec<u8>> {
+    let mut hash = vec![0u8; SHA256_LEN];
+
+    for (n, c) in s.bytes().rev().enumerate() {
+        let digit = BASE32_CHARS.iter().position(|b| *b == c)? as u16;
+
+        let b = n * 5;
+        let i = b / 8;
+        let j = b % 8;
+
+        let shifted = digit << j;
+
+        hash[i] |= shifted as u8;
+
+        if i + 1 < SHA256_LEN {
+            hash[i + 1] |= (shifted >> 8) as u8;
+        } else if shifted >> 8 != 0 {
+            return None;
+        }
+    }
+
+    Some(hash)
+}
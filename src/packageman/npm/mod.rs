@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use packagejson::{read_lockfile, read_package_json, PackageJson};
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod packagejson;
+
+const MODULE_DIRS: [&str; 2] = ["/usr/local/lib/node_modules", "/usr/lib/node_modules"];
+
+pub fn load_npm(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for module_dir in module_dirs() {
+        if debug > 0 {
+            eprintln!("Scanning npm packages in {}", module_dir.display());
+        }
+
+        // Read versions from the hidden lockfile if present
+        let locked = read_lockfile(&module_dir, debug);
+
+        // Directory binaries are linked in to (eg. /usr/local/lib/node_modules -> /usr/local/bin)
+        let bin_dir = module_dir
+            .parent()
+            .and_then(Path::parent)
+            .map(|prefix| prefix.join("bin"));
+
+        for package_dir in package_dirs(&module_dir)? {
+            match read_package_json(&package_dir) {
+                Ok(package_json) => {
+                    let package_elem = packages.len();
+
+                    packages.push(npm_package(
+                        &package_json.name,
+                        &package_version(&module_dir, &package_dir, &package_json, &locked, debug),
+                    ));
+
+                    // Claim the package directory
+                    if package_dir.is_symlink() {
+                        // Linked package (npm link)
+                        files.push(PackageFile::new(
+                            package_dir.clone(),
+                            Some(package_elem),
+                            None,
+                            None,
+                            None,
+                            None,
+                        ));
+                    } else {
+                        files.push(PackageFile::new_tree(
+                            package_dir.clone(),
+                            Some(package_elem),
+                        ));
+                    }
+
+                    // Claim binary links
+                    if let Some(bin_dir) = &bin_dir {
+                        for bin in &package_json.bins {
+                            files.push(PackageFile::new(
+                                bin_dir.join(bin),
+                                Some(package_elem),
+                                None,
+                                None,
+                                None,
+                                None,
+                            ));
+                        }
+                    }
+
+                    if debug > 1 {
+                        eprintln!(
+                            "{} owns {} and {} binaries",
+                            packages[package_elem].name_ver_arch(),
+                            package_dir.display(),
+                            package_json.bins.len()
+                        );
+                    }
+                }
+                Err(e) => eprintln!(
+                    "ERROR: Failed to read npm package {} ({e})",
+                    package_dir.display()
+                ),
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} npm packages found", packages.len());
+    }
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn npm_available() -> bool {
+    !module_dirs().is_empty()
+}
+
+fn module_dirs() -> Vec<PathBuf> {
+    MODULE_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir() && !dir.is_symlink())
+        .collect()
+}
+
+/// Lists package directories in a node_modules directory, descending in to @scope directories
+fn package_dirs(module_dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut dirs = Vec::new();
+
+    for ent in fs::read_dir(module_dir)?.filter_map(|ent| ent.ok()) {
+        let name = ent.file_name();
+        let name = name.to_string_lossy();
+
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if name.starts_with('@') {
+            if let Ok(scoped) = fs::read_dir(ent.path()) {
+                dirs.extend(scoped.filter_map(|ent| ent.ok()).map(|ent| ent.path()));
+            }
+        } else {
+            dirs.push(ent.path());
+        }
+    }
+
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+/// Gets the installed version of a package, preferring the lockfile
+fn package_version(
+    module_dir: &Path,
+    package_dir: &Path,
+    package_json: &PackageJson,
+    locked: &HashMap<PathBuf, String>,
+    debug: u8,
+) -> String {
+    let rel_dir = package_dir.strip_prefix(module_dir).unwrap_or(package_dir);
+
+    match locked.get(rel_dir) {
+        Some(version) => {
+            if debug > 0 && *version != package_json.version {
+                eprintln!(
+                    "npm package {} is version {} but locked at {}",
+                    package_json.name, package_json.version, version
+                );
+            }
+
+            version.clone()
+        }
+        None => package_json.version.clone(),
+    }
+}
+
+fn npm_package(name: &str, version: &str) -> Package {
+    let fullname = if version.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name}-{version}")
+    };
+
+    Package::new(
+        OsString::from(fullname),
+        OsString::from(name),
+        OsString::from(version),
+        None,
+        Origin::Source(Source::Npm),
+    )
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+/// Details read from a package's package.json
+pub struct PackageJson {
+    pub name: String,
+    pub version: String,
+    pub bins: Vec<String>,
+}
+
+pub fn read_package_json(package_dir: &Path) -> Result<PackageJson, Box<dyn Error>> {
+    let contents = fs::read(package_dir.join("package.json"))?;
+    let json: Value = serde_json::from_slice(&contents)?;
+
+    let name = json["name"]
+        .as_str()
+        .ok_or("No name in package.json")?
+        .to_string();
+
+    let version = json["version"].as_str().unwrap_or_default().to_string();
+
+    // Binaries are either a single path named after the package or a map of name to path
+    let bins = match &json["bin"] {
+        Value::String(_) => vec![name.rsplit('/').next().unwrap_or(&name).to_string()],
+        Value::Object(map) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(PackageJson {
+        name,
+        version,
+        bins,
+    })
+}
+
+/// Reads the hidden lockfile (.package-lock.json) from a node_modules directory,
+/// returning a map of package directory (relative to node_modules) to version
+pub fn read_lockfile(module_dir: &Path, debug: u8) -> HashMap<PathBuf, String> {
+    let lockfile = module_dir.join(".package-lock.json");
+
+    let json = match fs::read(&lockfile)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_slice::<Value>(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(json) => json,
+        Err(e) => {
+            if debug > 1 {
+                eprintln!("No npm lockfile {} ({e})", lockfile.display());
+            }
+
+            return HashMap::new();
+        }
+    };
+
+    let Some(lock_packages) = json["packages"].as_object() else {
+        return HashMap::new();
+    };
+
+    lock_packages
+        .iter()
+        .filter_map(|(key, details)| {
+            let rel_dir = key.strip_prefix("node_modules/")?;
+
+            // Only top level packages
+            if rel_dir.contains("/node_modules/") {
+                return None;
+            }
+
+            let version = details["version"].as_str()?;
+
+            Some((PathBuf::from(rel_dir), version.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::packageman::PackageFile;
+
+/// Reads an opkg .list file. Each line is an absolute path, optionally followed by
+/// a tab and the target of a symbolic link
+pub fn read_file_list(
+    list_file: &Path,
+    package_elem: usize,
+    installed_time: Option<i64>,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let contents = fs::read_to_string(list_file)?;
+
+    let files = contents
+        .lines()
+        .filter_map(|line| line.split('\t').next())
+        .filter(|path| path.starts_with('/'))
+        .map(|path| {
+            let mut file = PackageFile::new(
+                PathBuf::from(path),
+                Some(package_elem),
+                None,
+                None,
+                None,
+                None,
+            );
+
+            file.set_installed(installed_time);
+
+            file
+        })
+        .collect();
+
+    Ok(files)
+}
@@ -0,0 +1,75 @@
+use std::{error::Error, ffi::OsString, path::Path};
+
+use filelist::read_file_list;
+use status::read_status;
+
+use super::{LoadResult, Origin, Package, PackageMgr};
+
+mod filelist;
+mod status;
+
+/// Possible opkg state directories (OpenWrt uses /usr/lib/opkg)
+const STATE_DIRS: [&str; 2] = ["/usr/lib/opkg", "/var/lib/opkg"];
+
+pub fn load_opkg(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let state_dir = state_dir().ok_or("No opkg state directory found")?;
+
+    if debug > 0 {
+        eprintln!("Reading opkg status from {}", state_dir.display());
+    }
+
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for status in read_status(&state_dir.join("status"))? {
+        let package_elem = packages.len();
+
+        // Read the file list
+        let list_file = state_dir.join("info").join(format!("{}.list", status.name));
+
+        match read_file_list(&list_file, package_elem, status.installed_time) {
+            Ok(package_files) => {
+                if debug > 1 {
+                    eprintln!("{} files found in {}", package_files.len(), status.name);
+                }
+
+                files.extend(package_files);
+            }
+            Err(e) => eprintln!(
+                "ERROR: Failed to read opkg file list {} ({e})",
+                list_file.display()
+            ),
+        }
+
+        packages.push(Package::new(
+            OsString::from(format!("{}-{}", status.name, status.version)),
+            OsString::from(status.name),
+            OsString::from(status.version),
+            status.arch.map(OsString::from),
+            Origin::Mgr(PackageMgr::Opkg),
+        ));
+    }
+
+    if debug > 0 {
+        eprintln!("{} packages found", packages.len());
+    }
+
+    // Default ignores for opkg systems
+    let ignores = STATE_DIRS
+        .iter()
+        .map(|dir| format!("^{}($|/.*)", regex::escape(dir)))
+        .collect();
+
+    Ok((packages, files, ignores))
+}
+
+pub fn opkg_available() -> bool {
+    state_dir().is_some()
+}
+
+fn state_dir() -> Option<&'static Path> {
+    STATE_DIRS
+        .iter()
+        .map(Path::new)
+        .find(|dir| dir.join("status").is_file())
+}
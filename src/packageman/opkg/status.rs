@@ -0,0 +1,44 @@
+use std::{error::Error, fs, path::Path};
+
+/// An installed package from the opkg status file
+pub struct PackageStatus {
+    pub name: String,
+    pub version: String,
+    pub arch: Option<String>,
+    pub installed_time: Option<i64>,
+}
+
+/// Reads the opkg status file. Packages are described by paragraphs of
+/// "Field: value" lines separated by blank lines
+pub fn read_status(status_file: &Path) -> Result<Vec<PackageStatus>, Box<dyn Error>> {
+    let contents = fs::read_to_string(status_file)?;
+
+    let packages = contents
+        .split("\n\n")
+        .filter_map(|paragraph| {
+            let field = |name: &str| {
+                paragraph.lines().find_map(|line| {
+                    line.strip_prefix(name)
+                        .and_then(|rest| rest.strip_prefix(':'))
+                        .map(|value| value.trim().to_string())
+                })
+            };
+
+            // Only packages which are installed
+            let status = field("Status")?;
+
+            if !status.split(' ').any(|word| word == "installed") {
+                return None;
+            }
+
+            Some(PackageStatus {
+                name: field("Package")?,
+                version: field("Version").unwrap_or_default(),
+                arch: field("Architecture"),
+                installed_time: field("Installed-Time").and_then(|time| time.parse().ok()),
+            })
+        })
+        .collect();
+
+    Ok(packages)
+}
@@ -3,12 +3,15 @@ use std::{
     ffi::{OsStr, OsString},
 };
 
+use super::Origin;
+
 #[derive(Debug)]
 pub struct Package {
     fullname: OsString,
     name: OsString,
     version: OsString,
     arch: Option<OsString>,
+    origin: Origin,
 }
 
 impl Package {
@@ -17,12 +20,14 @@ impl Package {
         name: OsString,
         version: OsString,
         arch: Option<OsString>,
+        origin: Origin,
     ) -> Self {
         Self {
             fullname,
             name,
             version,
             arch,
+            origin,
         }
     }
 
@@ -30,45 +35,62 @@ impl Package {
         &self.fullname
     }
 
-    pub fn fullnamestr(&self) -> Cow<str> {
+    pub fn fullnamestr(&self) -> Cow<'_, str> {
         self.fullname.to_string_lossy()
     }
 
-    pub fn namestr(&self) -> Cow<str> {
+    pub fn namestr(&self) -> Cow<'_, str> {
         self.name.to_string_lossy()
     }
 
-    pub fn versionstr(&self) -> Cow<str> {
+    pub fn versionstr(&self) -> Cow<'_, str> {
         self.version.to_string_lossy()
     }
 
-    pub fn archstr(&self) -> Cow<str> {
+    pub fn archstr(&self) -> Cow<'_, str> {
         match &self.arch {
             Some(arch) => arch.to_string_lossy(),
             None => Cow::Borrowed(""),
         }
     }
 
+    pub fn arch(&self) -> Option<&OsStr> {
+        self.arch.as_deref()
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
     pub fn name_arch(&self) -> String {
         match &self.arch {
-            Some(arch) => format!("{}:{}", self.name.to_string_lossy(), arch.to_string_lossy()),
-            None => format!("{}", self.name.to_string_lossy(),),
+            Some(_) => format!("{}{}:{}", self.prefix(), self.namestr(), self.archstr()),
+            None => format!("{}{}", self.prefix(), self.namestr()),
         }
     }
 
     pub fn name_ver_arch(&self) -> String {
+        if self.version.is_empty() {
+            return self.name_arch();
+        }
+
         match &self.arch {
-            Some(arch) => format!(
-                "{}-{}:{}",
-                self.name.to_string_lossy(),
-                self.version.to_string_lossy(),
-                arch.to_string_lossy()
-            ),
-            None => format!(
-                "{}-{}",
-                self.name.to_string_lossy(),
-                self.version.to_string_lossy(),
+            Some(_) => format!(
+                "{}{}-{}:{}",
+                self.prefix(),
+                self.namestr(),
+                self.versionstr(),
+                self.archstr()
             ),
+            None => format!("{}{}-{}", self.prefix(), self.namestr(), self.versionstr()),
+        }
+    }
+
+    fn prefix(&self) -> String {
+        // Packages not from the primary package manager are prefixed with their origin
+        match self.origin {
+            Origin::Mgr(_) => String::new(),
+            origin => format!("{origin}:"),
         }
     }
 }
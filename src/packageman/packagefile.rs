@@ -1,13 +1,56 @@
 use std::path::{Path, PathBuf};
 
+/// File is a configuration file (rpm %config, dpkg conffile)
+pub const FILE_FLAG_CONFIG: u32 = 1 << 0;
+/// File is documentation (rpm %doc)
+pub const FILE_FLAG_DOC: u32 = 1 << 1;
+/// File is owned but its contents are not packaged (rpm %ghost)
+pub const FILE_FLAG_GHOST: u32 = 1 << 6;
+
+/// Installation state of a packaged file (as rpm FILESTATES)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileState {
+    #[default]
+    Normal,
+    /// Replaced by a file from another package
+    Replaced,
+    /// Not installed, eg. excluded documentation or languages
+    NotInstalled,
+    /// Not installed as it is on a shared network filesystem
+    NetShared,
+    /// Not installed as it is for a different architecture (multilib)
+    WrongColor,
+}
+
+impl FileState {
+    /// Converts an rpm FILESTATES value
+    pub fn from_rpm(state: i32) -> Self {
+        match state {
+            1 => FileState::Replaced,
+            2 => FileState::NotInstalled,
+            3 => FileState::NetShared,
+            4 => FileState::WrongColor,
+            _ => FileState::Normal,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PackageFile {
     path: PathBuf,
+    original: Option<PathBuf>,
     package: Option<usize>,
     size: Option<usize>,
     mode: Option<u32>,
     chksum: Option<Vec<u8>>,
     time: Option<i64>,
+    installed: Option<i64>,
+    owner: Option<(u32, u32)>,
+    link: Option<PathBuf>,
+    tree: bool,
+    excluded: bool,
+    flags: u32,
+    state: FileState,
 }
 
 impl PackageFile {
@@ -21,11 +64,38 @@ impl PackageFile {
     ) -> Self {
         Self {
             path,
+            original: None,
             package,
             size,
             mode,
             chksum,
             time,
+            installed: None,
+            owner: None,
+            link: None,
+            tree: false,
+            excluded: false,
+            flags: 0,
+            state: FileState::Normal,
+        }
+    }
+
+    /// Creates a directory whose whole contents are owned by the package
+    pub fn new_tree(path: PathBuf, package: Option<usize>) -> Self {
+        Self::new_tree_with_digest(path, package, None, None)
+    }
+
+    /// Creates a directory whose whole contents are owned by the package, with the
+    /// size and SHA256 of its NAR (Nix archive) serialisation
+    pub fn new_tree_with_digest(
+        path: PathBuf,
+        package: Option<usize>,
+        size: Option<usize>,
+        chksum: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            tree: true,
+            ..Self::new(path, package, size, None, chksum, None)
         }
     }
 
@@ -33,10 +103,75 @@ impl PackageFile {
         &self.path
     }
 
+    /// The path the package installs the file to, before any relocation
+    pub fn original_path(&self) -> &Path {
+        self.original.as_deref().unwrap_or(&self.path)
+    }
+
     pub fn package(&self) -> &Option<usize> {
         &self.package
     }
 
+    pub fn rebase_package(&mut self, offset: usize) {
+        if let Some(package) = &mut self.package {
+            *package += offset;
+        }
+    }
+
+    /// Moves the file to a different path, eg. when diverted
+    pub fn relocate(&mut self, path: PathBuf) {
+        self.original
+            .get_or_insert(std::mem::replace(&mut self.path, path));
+    }
+
+    /// Marks the file as intentionally not installed, eg. by dpkg path-exclude
+    pub fn exclude(&mut self) {
+        self.excluded = true;
+    }
+
+    /// Sets the file flags (FILE_FLAG_*)
+    pub fn set_flags(&mut self, flags: u32) {
+        self.flags = flags;
+    }
+
+    pub fn set_state(&mut self, state: FileState) {
+        self.state = state;
+    }
+
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = Some(mode);
+    }
+
+    /// Sets the time the package was installed, for package managers which don't record
+    /// per-file modification times
+    pub fn set_installed(&mut self, installed: Option<i64>) {
+        self.installed = installed;
+    }
+
+    /// Sets the packaged user and group IDs
+    pub fn set_owner(&mut self, owner: Option<(u32, u32)>) {
+        self.owner = owner;
+    }
+
+    /// Sets the packaged target of a symbolic link
+    pub fn set_link(&mut self, link: Option<PathBuf>) {
+        self.link = link;
+    }
+
+    /// Fills in the size, mode, time, owner and link target from another record of the same
+    /// file, eg. read from the package archive. An existing checksum is kept
+    pub fn fill_from(&mut self, other: PackageFile) {
+        self.size = other.size.or(self.size);
+        self.mode = other.mode.or(self.mode);
+        self.time = other.time.or(self.time);
+        self.owner = other.owner.or(self.owner);
+        self.link = other.link.or(self.link.take());
+
+        if self.chksum.is_none() {
+            self.chksum = other.chksum;
+        }
+    }
+
     pub fn size(&self) -> &Option<usize> {
         &self.size
     }
@@ -52,4 +187,40 @@ impl PackageFile {
     pub fn time(&self) -> &Option<i64> {
         &self.time
     }
+
+    pub fn installed(&self) -> &Option<i64> {
+        &self.installed
+    }
+
+    pub fn owner(&self) -> &Option<(u32, u32)> {
+        &self.owner
+    }
+
+    pub fn link(&self) -> Option<&Path> {
+        self.link.as_deref()
+    }
+
+    pub fn is_tree(&self) -> bool {
+        self.tree
+    }
+
+    pub fn is_excluded(&self) -> bool {
+        self.excluded
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    pub fn is_config(&self) -> bool {
+        self.flags & FILE_FLAG_CONFIG != 0
+    }
+
+    pub fn is_ghost(&self) -> bool {
+        self.flags & FILE_FLAG_GHOST != 0
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
 }
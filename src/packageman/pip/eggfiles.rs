@@ -0,0 +1,18 @@
+use std::{error::Error, fs, path::Path};
+
+use super::{listed_path, new_file};
+use crate::packageman::PackageFile;
+
+/// Reads installed-files.txt from an egg-info directory.
+/// Each line is a path relative to the egg-info directory
+pub fn egg_files(egg_info: &Path, package_elem: usize) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let contents = fs::read_to_string(egg_info.join("installed-files.txt"))?;
+
+    let files = contents
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| new_file(listed_path(egg_info, line), package_elem, None, None))
+        .collect();
+
+    Ok(files)
+}
@@ -0,0 +1,191 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eggfiles::egg_files;
+use record::record_files;
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+mod eggfiles;
+mod record;
+
+const LIB_DIRS: [&str; 4] = [
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local/lib",
+    "/usr/local/lib64",
+];
+
+/// INSTALLER file contents of pip and pip compatible installers. Other tools, eg. rpm's
+/// %pyproject_install, write their own name
+const PIP_INSTALLERS: [&str; 2] = ["pip", "uv"];
+
+pub fn load_pip(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for site_dir in site_dirs() {
+        if debug > 0 {
+            eprintln!("Scanning Python packages in {}", site_dir.display());
+        }
+
+        let dir = match fs::read_dir(&site_dir) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("ERROR: Failed to read {} ({e})", site_dir.display());
+                continue;
+            }
+        };
+
+        let mut ents = dir
+            .filter_map(|ent| ent.ok())
+            .map(|ent| ent.path())
+            .collect::<Vec<_>>();
+
+        ents.sort();
+
+        for ent in ents {
+            let Some(file_name) = ent
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+            else {
+                continue;
+            };
+
+            // Get package details and file list
+            let (stem, package_files) = if let Some(stem) = file_name.strip_suffix(".dist-info") {
+                // Skip distributions installed by a system package manager
+                let installer = fs::read_to_string(ent.join("INSTALLER")).unwrap_or_default();
+
+                if !PIP_INSTALLERS.contains(&installer.trim()) {
+                    if debug > 1 {
+                        eprintln!(
+                            "Skipping {} (installer '{}')",
+                            ent.display(),
+                            installer.trim()
+                        );
+                    }
+
+                    continue;
+                }
+
+                (stem, record_files(&ent, packages.len(), debug))
+            } else if let Some(stem) = file_name.strip_suffix(".egg-info") {
+                // Only installs by pip record their file list
+                if !ent.join("installed-files.txt").exists() {
+                    if debug > 1 {
+                        eprintln!("Skipping {} (no installed-files.txt)", ent.display());
+                    }
+
+                    continue;
+                }
+
+                (stem, egg_files(&ent, packages.len()))
+            } else {
+                continue;
+            };
+
+            match package_files {
+                Ok(package_files) => {
+                    let package = distribution_package(stem);
+
+                    if debug > 1 {
+                        eprintln!(
+                            "{} files found in {}",
+                            package_files.len(),
+                            package.name_ver_arch()
+                        );
+                    }
+
+                    packages.push(package);
+                    files.extend(package_files);
+                }
+                Err(e) => eprintln!(
+                    "ERROR: Failed to get Python file list for {} ({e})",
+                    ent.display()
+                ),
+            }
+        }
+    }
+
+    if debug > 0 {
+        eprintln!("{} Python packages found", packages.len());
+    }
+
+    Ok((packages, files, Vec::new()))
+}
+
+pub fn pip_available() -> bool {
+    !site_dirs().is_empty()
+}
+
+/// Finds site-packages and dist-packages directories for installed Python versions
+fn site_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    for lib_dir in LIB_DIRS {
+        let Ok(ents) = fs::read_dir(lib_dir) else {
+            continue;
+        };
+
+        let mut python_dirs = ents
+            .filter_map(|ent| ent.ok())
+            .filter(|ent| ent.file_name().to_string_lossy().starts_with("python"))
+            .map(|ent| ent.path())
+            .collect::<Vec<_>>();
+
+        python_dirs.sort();
+
+        for python_dir in python_dirs {
+            for site in ["site-packages", "dist-packages"] {
+                let site_dir = python_dir.join(site);
+
+                if site_dir.is_dir() && !site_dir.is_symlink() {
+                    dirs.push(site_dir);
+                }
+            }
+        }
+    }
+
+    dirs
+}
+
+/// Builds a package from a dist-info (name-version) or egg-info (name-version-pyX.Y) stem
+fn distribution_package(stem: &str) -> Package {
+    let mut split = stem.splitn(3, '-');
+
+    let name = split.next().unwrap_or_default();
+    let version = split.next().unwrap_or_default();
+
+    let fullname = if version.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name}-{version}")
+    };
+
+    Package::new(
+        OsString::from(fullname),
+        OsString::from(name),
+        OsString::from(version),
+        None,
+        Origin::Source(Source::Pip),
+    )
+}
+
+/// Resolves a path listed in a metadata file relative to the directory it is relative to
+fn listed_path(base: &Path, path: &str) -> PathBuf {
+    super::normalise_path(&base.join(path))
+}
+
+fn new_file(
+    path: PathBuf,
+    package_elem: usize,
+    size: Option<usize>,
+    chksum: Option<Vec<u8>>,
+) -> PackageFile {
+    PackageFile::new(path, Some(package_elem), size, None, chksum, None)
+}
@@ -0,0 +1,85 @@
+use std::{error::Error, fs, path::Path};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD_INDIFFERENT, Engine};
+
+use super::{listed_path, new_file};
+use crate::packageman::PackageFile;
+
+/// Reads the RECORD file from a dist-info directory.
+/// Each line is a CSV record of path, hash (sha256=<urlsafe base64>) and size.
+/// Paths are relative to the site-packages directory
+pub fn record_files(
+    dist_info: &Path,
+    package_elem: usize,
+    debug: u8,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let contents = fs::read_to_string(dist_info.join("RECORD"))?;
+
+    let site_dir = dist_info.parent().ok_or("No site directory")?;
+
+    let files = contents
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let fields = split_csv(line);
+
+            let path = fields.first()?;
+
+            // Decode hash
+            let chksum = fields.get(1).and_then(|hash| {
+                let (algorithm, digest) = hash.split_once('=')?;
+
+                if algorithm != "sha256" {
+                    if debug > 2 {
+                        eprintln!("Unsupported hash algorithm {algorithm} for {path}");
+                    }
+
+                    return None;
+                }
+
+                URL_SAFE_NO_PAD_INDIFFERENT.decode(digest).ok()
+            });
+
+            // Parse size
+            let size = fields.get(2).and_then(|size| size.parse::<usize>().ok());
+
+            Some(new_file(
+                listed_path(site_dir, path),
+                package_elem,
+                size,
+                chksum,
+            ))
+        })
+        .collect();
+
+    Ok(files)
+}
+
+/// Splits a CSV line in to fields, handling double quoted fields
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    // Escaped quote
+                    field.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    fields.push(field);
+
+    fields
+}
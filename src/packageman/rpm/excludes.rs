@@ -0,0 +1,61 @@
+use std::process::Command;
+
+use crate::packageman::{FileState, PackageFile, FILE_FLAG_DOC};
+
+/// rpm settings which cause package files not to be installed
+pub struct RpmExcludes {
+    /// %_excludedocs is set
+    docs: bool,
+    /// Languages allowed by %_install_langs, or None for all
+    langs: Option<Vec<String>>,
+}
+
+impl RpmExcludes {
+    /// Reads the settings from the rpm macro configuration
+    pub fn load(debug: u8) -> Self {
+        let output = Command::new("rpm")
+            .arg("--eval")
+            .arg("%{?_excludedocs}\n%{?_install_langs}")
+            .output();
+
+        let stdout = match output {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            _ => String::new(),
+        };
+
+        let mut lines = stdout.lines();
+
+        let docs = lines
+            .next()
+            .is_some_and(|value| value.trim().parse::<i32>().is_ok_and(|value| value != 0));
+
+        let langs = match lines.next().map(|value| value.trim()) {
+            None | Some("") | Some("all") => None,
+            Some(value) => Some(value.split(':').map(|lang| lang.to_string()).collect()),
+        };
+
+        if debug > 0 {
+            eprintln!("rpm excludedocs {docs}, install_langs {langs:?}");
+        }
+
+        Self { docs, langs }
+    }
+
+    /// Returns true if the file was not installed, either according to its state or the settings
+    pub fn is_excluded(&self, file: &PackageFile, lang: &str) -> bool {
+        if matches!(file.state(), FileState::NotInstalled | FileState::NetShared)
+            || (self.docs && file.flags() & FILE_FLAG_DOC != 0)
+        {
+            return true;
+        }
+
+        match &self.langs {
+            Some(langs) if !lang.is_empty() && lang != "C" => !langs
+                .iter()
+                .any(|allowed| lang.starts_with(allowed.as_str())),
+            _ => false,
+        }
+    }
+}
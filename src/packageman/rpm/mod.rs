@@ -1,14 +1,29 @@
 use rayon::prelude::*;
-use std::{error::Error, process::Command, sync::Mutex};
-
-use rpmdump::get_rpm_dump;
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+};
+
+use excludes::RpmExcludes;
+use rpmdump::get_rpm_files;
 use rpmlist::get_rpm_list;
 
-use super::LoadResult;
+use super::{alternatives::load_alternatives, LoadResult, Package};
 
+mod excludes;
 mod rpmdump;
 mod rpmlist;
 
+/// alternatives (chkconfig) administrative directory
+const ALTERNATIVES_DIR: &str = "/var/lib/alternatives";
+
+/// Package caches of dnf, dnf5 and yum
+const CACHE_DIRS: [&str; 3] = ["/var/cache/dnf", "/var/cache/libdnf5", "/var/cache/yum"];
+
 pub fn load_rpm(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
     // Get list of RPMs
     let rpms = get_rpm_list(debug)?;
@@ -18,6 +33,9 @@ pub fn load_rpm(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
         eprintln!("Getting RPM file list");
     }
 
+    // Get settings which cause files not to be installed
+    let excludes = RpmExcludes::load(debug);
+
     let rpm_files_mutex = Mutex::new(Vec::new());
 
     rpms.par_iter().enumerate().for_each(|(rpm_elem, rpm)| {
@@ -26,7 +44,7 @@ pub fn load_rpm(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
         }
 
         // Get RPM contents
-        match get_rpm_dump(rpm, rpm_elem) {
+        match get_rpm_files(rpm, rpm_elem, &excludes) {
             Ok(this_rpm_files) => {
                 if debug > 1 {
                     eprintln!(
@@ -50,12 +68,16 @@ pub fn load_rpm(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
         }
     });
 
-    let rpm_files = rpm_files_mutex.into_inner().unwrap();
+    let mut rpm_files = rpm_files_mutex.into_inner().unwrap();
 
     if debug > 0 {
         eprintln!("{} files found", rpm_files.len());
     }
 
+    // Add alternatives symbolic links
+    let alt_files = load_alternatives(Path::new(ALTERNATIVES_DIR), &rpm_files, debug);
+    rpm_files.extend(alt_files);
+
     // Default ignores for RPM systems
     let ignores = vec![
         "^/usr/share/man($|/.*)".into(),
@@ -71,3 +93,40 @@ pub fn rpm_available() -> bool {
         _ => false,
     }
 }
+
+/// Finds the .rpm for an installed package in the dnf or yum package caches
+pub fn cached_rpm(package: &Package) -> Option<PathBuf> {
+    let mut file_name = OsString::from(package.fullname());
+
+    if let Some(arch) = package.arch() {
+        file_name.push(".");
+        file_name.push(arch);
+    }
+
+    file_name.push(".rpm");
+
+    CACHE_DIRS
+        .iter()
+        .find_map(|dir| find_file(Path::new(dir), &file_name))
+}
+
+/// Recursively searches a directory for a file
+fn find_file(dir: &Path, file_name: &OsString) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+
+    for ent in fs::read_dir(dir).ok()?.filter_map(|ent| ent.ok()) {
+        let Ok(file_type) = ent.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            subdirs.push(ent.path());
+        } else if file_type.is_file() && ent.file_name() == *file_name {
+            return Some(ent.path());
+        }
+    }
+
+    subdirs
+        .into_iter()
+        .find_map(|subdir| find_file(&subdir, file_name))
+}
@@ -7,13 +7,24 @@ use std::str;
 
 use unix_mode::is_file;
 
-use crate::packageman::{decode_hex, Package, PackageFile};
+use crate::packageman::{decode_hex, FileState, Package, PackageFile};
 
-pub fn get_rpm_dump(rpm: &Package, rpm_elem: usize) -> Result<Vec<PackageFile>, Box<dyn Error>> {
-    // Run rpm -q --dump to get list of rpm files
+use super::excludes::RpmExcludes;
+
+/// rpm query format listing the details of each file. The name is last as it may contain tabs
+const FILE_FORMAT: &str = "[%{FILESIZES}\t%{FILEMTIMES}\t%{FILEDIGESTS}\t%{FILEMODES:octal}\t\
+    %{FILESTATES}\t%{FILEFLAGS}\t%{FILELANGS}\t%{FILENAMES}\n]";
+
+pub fn get_rpm_files(
+    rpm: &Package,
+    rpm_elem: usize,
+    excludes: &RpmExcludes,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    // Run rpm -q with a query format to get list of rpm files
     let output = Command::new("rpm")
         .arg("-q")
-        .arg("--dump")
+        .arg("--queryformat")
+        .arg(FILE_FORMAT)
         .arg(rpm.fullname())
         .output()?;
 
@@ -22,86 +33,70 @@ pub fn get_rpm_dump(rpm: &Package, rpm_elem: usize) -> Result<Vec<PackageFile>,
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprintln!("{}", stderr);
         Err(format!(
-            "rpm package dump for {} returned {}",
+            "rpm file query for {} returned {}",
             rpm.fullnamestr(),
             output.status
         ))?
     }
 
-    // Return rpm dump details
-    let rpm_files = output
+    // Parse file details
+    output
         .stdout
         .split(|c| *c == 0x0a)
-        .filter(|line| !line.is_empty() && line[0] != b'(') // Handle '(contains no files)'
-        .map(|line| parse_line(rpm_elem, line))
-        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
-
-    Ok(rpm_files)
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_line(rpm_elem, line, excludes))
+        .collect()
 }
 
-fn parse_line(rpm_elem: usize, line: &[u8]) -> Result<PackageFile, Box<dyn Error>> {
+fn parse_line(
+    rpm_elem: usize,
+    line: &[u8],
+    excludes: &RpmExcludes,
+) -> Result<PackageFile, Box<dyn Error>> {
     // Terms are:
-    //   File name (may contain spaces grr)
     //   File size
     //   Last modified date (seconds since 01/01/1970)
-    //   Checksum (MD5/SHA256)
-    //   File mode
-    //   Owner
-    //   Group
-    //   Config file 0/1
-    //   Documentation file 0/1
-    //   Major/minor device number
-    //   Link target, or X (may contain spaces grr)
-
-    // Find spaces in the line
-    let spcpos = line
-        .iter()
-        .enumerate()
-        .filter(|(_, c)| **c == b' ')
-        .map(|(i, _)| i)
-        .collect::<Vec<_>>();
-
-    let term_cnt = spcpos.len() + 1;
-
-    let get_term = |i: usize| -> &[u8] {
-        let start = if i == 0 { 0 } else { spcpos[i - 1] + 1 };
-
-        let end = if i == spcpos.len() {
-            line.len()
-        } else {
-            spcpos[i]
-        };
-
-        &line[start..end]
+    //   Checksum (MD5/SHA256), empty for non-regular files
+    //   File mode (octal)
+    //   File state
+    //   File flags
+    //   File language, may be empty
+    //   File name (may contain tabs)
+    let mut split = line.splitn(8, |c| *c == b'\t');
+
+    let mut next_term = |name: &str| -> Result<&str, Box<dyn Error>> {
+        let term = split
+            .next()
+            .ok_or_else(|| format!("Missing file {name} in '{}'", String::from_utf8_lossy(line)))?;
+
+        Ok(str::from_utf8(term)?)
     };
 
-    // Break out file name
-    let path = PathBuf::from(OsStr::from_bytes(&line[..spcpos[term_cnt - 11]]));
+    let size_str = next_term("size")?;
+    let time_str = next_term("time")?;
+    let chksum_str = next_term("checksum")?;
+    let mode_str = next_term("mode")?;
+    let state_str = next_term("state")?;
+    let flags_str = next_term("flags")?;
+    let lang = next_term("language")?.to_string();
 
-    // Get mode
-    let mode_slice = get_term(term_cnt - 7);
+    let path = PathBuf::from(OsStr::from_bytes(split.next().ok_or_else(|| {
+        format!("Missing file name in '{}'", String::from_utf8_lossy(line))
+    })?));
 
-    let mode = u32::from_str_radix(str::from_utf8(mode_slice)?, 8).map_err(|e| {
-        format!(
-            "Failed to parse file mode in '{}': {e}",
-            String::from_utf8_lossy(mode_slice)
-        )
-    })?;
+    // Get mode
+    let mode = u32::from_str_radix(mode_str, 8)
+        .map_err(|e| format!("Failed to parse file mode '{mode_str}': {e}"))?;
 
     let (size, chksum) = if is_file(mode) {
         // Get size
-        let size_slice = get_term(term_cnt - 10);
-
-        let size = Some(str::from_utf8(size_slice)?.parse::<usize>().map_err(|e| {
-            format!(
-                "Failed to parse size '{}': {e}",
-                String::from_utf8_lossy(size_slice)
-            )
-        })?);
+        let size = Some(
+            size_str
+                .parse::<usize>()
+                .map_err(|e| format!("Failed to parse size '{size_str}': {e}"))?,
+        );
 
         // Get checksum
-        let chksum_str = str::from_utf8(get_term(term_cnt - 8))?;
-
         let chksum = if chksum_str.chars().any(|c| c != '0') {
             Some(decode_hex(chksum_str)?)
         } else {
@@ -114,21 +109,30 @@ fn parse_line(rpm_elem: usize, line: &[u8]) -> Result<PackageFile, Box<dyn Error
     };
 
     // Get time
-    let time_slice = get_term(term_cnt - 9);
-
-    let time = Some(str::from_utf8(time_slice)?.parse::<i64>().map_err(|e| {
-        format!(
-            "Failed to parse time '{}': {e}",
-            String::from_utf8_lossy(time_slice)
-        )
-    })?);
-
-    Ok(PackageFile::new(
-        path,
-        Some(rpm_elem),
-        size,
-        Some(mode),
-        chksum,
-        time,
-    ))
+    let time = Some(
+        time_str
+            .parse::<i64>()
+            .map_err(|e| format!("Failed to parse time '{time_str}': {e}"))?,
+    );
+
+    let state = state_str
+        .parse::<i32>()
+        .map_err(|e| format!("Failed to parse file state '{state_str}': {e}"))?;
+
+    let flags = flags_str
+        .parse::<u32>()
+        .map_err(|e| format!("Failed to parse file flags '{flags_str}': {e}"))?;
+
+    let mut file = PackageFile::new(path, Some(rpm_elem), size, Some(mode), chksum, time);
+
+    // rpm file flags share the FILE_FLAG_* values
+    file.set_flags(flags);
+    file.set_state(FileState::from_rpm(state));
+
+    // Mark files which were intentionally not installed
+    if excludes.is_excluded(&file, &lang) {
+        file.exclude();
+    }
+
+    Ok(file)
 }
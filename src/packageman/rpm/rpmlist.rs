@@ -3,7 +3,7 @@ use std::ffi::OsString;
 use std::os::unix::ffi::OsStringExt;
 use std::process::Command;
 
-use crate::packageman::Package;
+use crate::packageman::{Origin, Package, PackageMgr};
 
 pub fn get_rpm_list(debug: u8) -> Result<Vec<Package>, Box<dyn Error>> {
     if debug > 0 {
@@ -62,7 +62,13 @@ pub fn get_rpm_list(debug: u8) -> Result<Vec<Package>, Box<dyn Error>> {
                 fullname.push(version);
             }
 
-            Package::new(fullname, name, version.unwrap_or_else(OsString::new), arch)
+            Package::new(
+                fullname,
+                name,
+                version.unwrap_or_else(OsString::new),
+                arch,
+                Origin::Mgr(PackageMgr::Rpm),
+            )
         })
         .collect::<Vec<_>>();
 
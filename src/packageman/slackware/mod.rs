@@ -0,0 +1,133 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use pkglog::read_package_log;
+use scriptlog::read_script_links;
+
+use super::{LoadResult, Origin, Package, PackageFile, PackageMgr};
+
+mod pkglog;
+mod scriptlog;
+
+/// Installed package logs (/var/log/packages is a link to here on Slackware 15)
+const PACKAGE_DIRS: [&str; 2] = ["/var/lib/pkgtools/packages", "/var/log/packages"];
+
+/// Install script logs
+const SCRIPT_DIRS: [&str; 2] = ["/var/lib/pkgtools/scripts", "/var/log/scripts"];
+
+pub fn load_slackware(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let package_dir = first_dir(&PACKAGE_DIRS).ok_or("No Slackware package directory found")?;
+    let script_dir = first_dir(&SCRIPT_DIRS);
+
+    if debug > 0 {
+        eprintln!("Reading Slackware packages from {}", package_dir.display());
+    }
+
+    let mut logs = fs::read_dir(&package_dir)?
+        .filter_map(|ent| ent.ok())
+        .map(|ent| ent.path())
+        .collect::<Vec<_>>();
+
+    logs.sort();
+
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for log in logs {
+        let Some(fullname) = log
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+
+        let package_elem = packages.len();
+
+        match read_package_log(&log, package_elem) {
+            Ok(package_files) => {
+                if debug > 1 {
+                    eprintln!("{} files found in {}", package_files.len(), fullname);
+                }
+
+                files.extend(package_files);
+            }
+            Err(e) => {
+                eprintln!("ERROR: Failed to read package log {} ({e})", log.display());
+                continue;
+            }
+        }
+
+        // Symbolic links are created by the install script
+        if let Some(script_dir) = &script_dir {
+            let script = script_dir.join(&fullname);
+
+            if script.exists() {
+                match read_script_links(&script) {
+                    Ok(links) => files.extend(links.into_iter().map(|link| {
+                        PackageFile::new(
+                            link,
+                            Some(package_elem),
+                            None,
+                            Some(libc::S_IFLNK | 0o777),
+                            None,
+                            None,
+                        )
+                    })),
+                    Err(e) => eprintln!(
+                        "ERROR: Failed to read install script {} ({e})",
+                        script.display()
+                    ),
+                }
+            }
+        }
+
+        packages.push(slackware_package(fullname));
+    }
+
+    if debug > 0 {
+        eprintln!("{} packages found", packages.len());
+    }
+
+    // Default ignores for Slackware systems
+    let ignores = vec!["^/var/lib/pkgtools($|/.*)".into()];
+
+    Ok((packages, files, ignores))
+}
+
+pub fn slackware_available() -> bool {
+    first_dir(&PACKAGE_DIRS).is_some()
+}
+
+fn first_dir(dirs: &[&str]) -> Option<PathBuf> {
+    dirs.iter()
+        .map(Path::new)
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Builds a package from a package name of the form name-version-arch-build
+fn slackware_package(fullname: String) -> Package {
+    let mut split = fullname.rsplitn(4, '-');
+
+    let build = split.next().unwrap_or_default();
+    let arch = split.next();
+    let version = split.next().unwrap_or_default();
+    let name = split.next();
+
+    let (name, version, arch) = match (name, arch) {
+        (Some(name), Some(arch)) => (name, format!("{version}-{build}"), Some(arch)),
+        _ => (fullname.as_str(), String::new(), None),
+    };
+
+    Package::new(
+        OsString::from(&fullname),
+        OsString::from(name),
+        OsString::from(version),
+        arch.map(OsString::from),
+        Origin::Mgr(PackageMgr::Slackware),
+    )
+}
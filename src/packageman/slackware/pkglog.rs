@@ -0,0 +1,35 @@
+use std::{error::Error, fs, path::Path};
+
+use crate::packageman::PackageFile;
+
+/// Reads a package log. Installed paths are listed relative to / after the FILE LIST: line
+pub fn read_package_log(
+    log: &Path,
+    package_elem: usize,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let contents = fs::read(log)?;
+    let contents = String::from_utf8_lossy(&contents);
+
+    let files = contents
+        .lines()
+        .skip_while(|line| *line != "FILE LIST:")
+        .skip(1)
+        .map(|line| line.trim_end_matches('/'))
+        // Skip the root and the install scripts which are removed after installation
+        .filter(|line| {
+            !line.is_empty() && *line != "." && *line != "install" && !line.starts_with("install/")
+        })
+        .map(|line| {
+            PackageFile::new(
+                Path::new("/").join(line),
+                Some(package_elem),
+                None,
+                None,
+                None,
+                None,
+            )
+        })
+        .collect();
+
+    Ok(files)
+}
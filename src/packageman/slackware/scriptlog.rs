@@ -0,0 +1,26 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+/// Symbolic link creation in an install script: ( cd <dir> ; ln -sf <target> <link> )
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\( cd (\S+) ; ln -sf (\S+) (\S+) \)").unwrap());
+
+/// Reads the symbolic links created by a package's install script
+pub fn read_script_links(script: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let contents = fs::read(script)?;
+    let contents = String::from_utf8_lossy(&contents);
+
+    let links = contents
+        .lines()
+        .filter_map(|line| LINK.captures(line))
+        .map(|captures| Path::new("/").join(&captures[1]).join(&captures[3]))
+        .collect();
+
+    Ok(links)
+}
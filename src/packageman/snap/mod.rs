@@ -0,0 +1,112 @@
+use std::{error::Error, ffi::OsString, fs, path::Path};
+
+use super::{LoadResult, Origin, Package, PackageFile, Source};
+
+/// Directory holding the installed snap images (<name>_<revision>.snap)
+const SNAPS_DIR: &str = "/var/lib/snapd/snaps";
+
+/// Directory the snap images are mounted under (<name>/<revision>)
+pub const MOUNT_DIR: &str = "/snap";
+
+/// Directory holding writable snap data (<name>)
+const DATA_DIR: &str = "/var/snap";
+
+pub fn load_snap(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let mut packages: Vec<Package> = Vec::new();
+    let mut files = Vec::new();
+
+    if debug > 0 {
+        eprintln!("Scanning snaps in {}", SNAPS_DIR);
+    }
+
+    let mut images = fs::read_dir(SNAPS_DIR)?
+        .filter_map(|ent| ent.ok())
+        .map(|ent| ent.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "snap"))
+        .collect::<Vec<_>>();
+
+    images.sort();
+
+    for image in images {
+        let Some((name, revision)) = image
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.rsplit_once('_'))
+            .map(|(name, revision)| (name.to_string(), revision.to_string()))
+        else {
+            continue;
+        };
+
+        // Each revision of a snap is kept as a separate image
+        let package_elem = match packages
+            .iter()
+            .position(|package| package.namestr() == name)
+        {
+            Some(package_elem) => package_elem,
+            None => {
+                let mount_dir = Path::new(MOUNT_DIR).join(&name);
+                let data_dir = Path::new(DATA_DIR).join(&name);
+
+                let version = snap_version(&mount_dir).unwrap_or_else(|| revision.clone());
+
+                if debug > 1 {
+                    eprintln!("Snap {name} version {version}");
+                }
+
+                // Mount and data directories are owned in their entirety
+                files.push(PackageFile::new_tree(mount_dir, Some(packages.len())));
+
+                if data_dir.is_dir() {
+                    files.push(PackageFile::new_tree(data_dir, Some(packages.len())));
+                }
+
+                packages.push(Package::new(
+                    OsString::from(format!("{name}-{version}")),
+                    OsString::from(&name),
+                    OsString::from(version),
+                    None,
+                    Origin::Source(Source::Snap),
+                ));
+
+                packages.len() - 1
+            }
+        };
+
+        files.push(PackageFile::new(
+            image,
+            Some(package_elem),
+            None,
+            None,
+            None,
+            None,
+        ));
+    }
+
+    if debug > 0 {
+        eprintln!("{} snaps found", packages.len());
+    }
+
+    // State managed by snapd
+    let ignores = vec![
+        "^/var/lib/snapd/(cache|cookie|sequence|seccomp|apparmor|mount|inhibit)($|/.*)".into(),
+        "^/var/lib/snapd/state\\.json$".into(),
+    ];
+
+    Ok((packages, files, ignores))
+}
+
+pub fn snap_available() -> bool {
+    Path::new(SNAPS_DIR).is_dir()
+}
+
+/// Reads the version from the current revision's meta/snap.yaml
+fn snap_version(mount_dir: &Path) -> Option<String> {
+    let snap_yaml = mount_dir.join("current").join("meta").join("snap.yaml");
+
+    let contents = fs::read_to_string(snap_yaml).ok()?;
+
+    contents.lines().find_map(|line| {
+        line.strip_prefix("version:")
+            .map(|version| version.trim().trim_matches(['\'', '"']).to_string())
+    })
+}
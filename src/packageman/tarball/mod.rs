@@ -0,0 +1,131 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub use tarentries::tar_files;
+
+use super::{external::write_stream, LoadResult, Origin, Package};
+use crate::archive::decompress;
+
+mod tarentries;
+
+const TAR_EXTENSIONS: [&str; 7] = [
+    ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.zst", ".tzst", ".tar",
+];
+
+/// A tar archive and the directory it was extracted in to
+#[derive(Clone, Debug)]
+pub struct TarballSpec {
+    archive: PathBuf,
+    prefix: PathBuf,
+}
+
+impl FromStr for TarballSpec {
+    type Err = String;
+
+    /// Parses ARCHIVE[=PREFIX]. The prefix defaults to /. The archive path may contain =, so the
+    /// prefix follows the last = unless the whole string names a file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (archive, prefix) = match s.rsplit_once('=') {
+            Some((archive, prefix)) if !Path::new(s).is_file() => (archive, prefix),
+            _ => (s, "/"),
+        };
+
+        if archive.is_empty() {
+            Err("No archive specified")?
+        }
+
+        let prefix = PathBuf::from(prefix);
+
+        if !prefix.is_absolute() {
+            Err(format!(
+                "Install prefix {} is not absolute",
+                prefix.display()
+            ))?
+        }
+
+        Ok(Self {
+            archive: PathBuf::from(archive),
+            prefix,
+        })
+    }
+}
+
+pub fn load_tarball(spec: &TarballSpec, debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    if debug > 0 {
+        eprintln!(
+            "Reading tarball {} installed in {}",
+            spec.archive.display(),
+            spec.prefix.display()
+        );
+    }
+
+    let package = tarball_package(&spec.archive);
+
+    let file = File::open(&spec.archive)
+        .map_err(|e| format!("Failed to open tarball {} ({e})", spec.archive.display()))?;
+
+    let mut files = tar_files(decompress(file)?, &spec.prefix, 0, true, debug)
+        .map_err(|e| format!("Failed to read tarball {} ({e})", spec.archive.display()))?;
+
+    // Extracted files are owned by whoever extracted them unless that was root, so the archive
+    // owners aren't checked
+    for file in &mut files {
+        file.set_owner(None);
+    }
+
+    if debug > 0 {
+        eprintln!("{} files found in {}", files.len(), package.name_arch());
+    }
+
+    Ok((vec![package], files, Vec::new()))
+}
+
+/// Writes a tarball's contents as an external package manifest
+pub fn tarball_manifest(
+    spec: &TarballSpec,
+    out: &mut impl Write,
+    debug: u8,
+) -> Result<(), Box<dyn Error>> {
+    let (packages, files, _) = load_tarball(spec, debug)?;
+
+    write_stream(&packages, &files, out)
+}
+
+/// Builds a package from the archive file name, splitting name-version where possible
+fn tarball_package(archive: &Path) -> Package {
+    let file_name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // Strip the archive extension
+    let fullname = TAR_EXTENSIONS
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .unwrap_or(&file_name)
+        .to_string();
+
+    // Split the version at the last hyphen followed by a digit
+    let (name, version) = match fullname
+        .match_indices('-')
+        .rev()
+        .find(|(pos, _)| fullname[pos + 1..].starts_with(|c: char| c.is_ascii_digit()))
+    {
+        Some((pos, _)) => (&fullname[..pos], &fullname[pos + 1..]),
+        None => (fullname.as_str(), ""),
+    };
+
+    Package::new(
+        OsString::from(&fullname),
+        OsString::from(name),
+        OsString::from(version),
+        None,
+        Origin::Tarball,
+    )
+}
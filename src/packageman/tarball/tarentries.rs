@@ -0,0 +1,145 @@
+use std::{
+    error::Error,
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+use tar::{Archive, EntryType};
+
+use crate::packageman::{
+    ids::{group_id, user_id},
+    PackageFile,
+};
+
+/// Reads the entries of a tar stream, returning them as package files installed under prefix.
+/// Regular file contents are only hashed if checksum is set
+pub fn tar_files(
+    reader: impl Read,
+    prefix: &Path,
+    package_elem: usize,
+    checksum: bool,
+    debug: u8,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let mut files = Vec::new();
+
+    let mut archive = Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        // Get the type bits for the entry
+        let file_type = match entry.header().entry_type() {
+            EntryType::Regular | EntryType::Continuous | EntryType::Link => libc::S_IFREG,
+            EntryType::Directory => libc::S_IFDIR,
+            EntryType::Symlink => libc::S_IFLNK,
+            EntryType::Char => libc::S_IFCHR,
+            EntryType::Block => libc::S_IFBLK,
+            EntryType::Fifo => libc::S_IFIFO,
+            other => {
+                if debug > 2 {
+                    eprintln!("Skipping tar entry type {:?}", other);
+                }
+                continue;
+            }
+        };
+
+        // Build the installed path
+        let entry_path = entry.path()?.into_owned();
+
+        let Some(path) = install_path(prefix, &entry_path) else {
+            if debug > 2 {
+                eprintln!("Skipping tar entry {}", entry_path.display());
+            }
+            continue;
+        };
+
+        // Build the mode
+        let mode = if file_type == libc::S_IFLNK {
+            file_type | 0o777
+        } else {
+            file_type | (entry.header().mode()? & 0o7777)
+        };
+
+        let time = Some(entry.header().mtime()? as i64);
+
+        // As in dpkg, owner names are used in preference to IDs if they exist locally
+        let header = entry.header();
+
+        let owner = Some((
+            header
+                .username()
+                .ok()
+                .flatten()
+                .and_then(user_id)
+                .unwrap_or(header.uid()? as u32),
+            header
+                .groupname()
+                .ok()
+                .flatten()
+                .and_then(group_id)
+                .unwrap_or(header.gid()? as u32),
+        ));
+
+        let link = if file_type == libc::S_IFLNK {
+            entry.link_name()?.map(|link| link.into_owned())
+        } else {
+            None
+        };
+
+        // Hard links have no content of their own
+        let (size, chksum) =
+            if file_type == libc::S_IFREG && entry.header().entry_type() != EntryType::Link {
+                let size = entry.header().size()? as usize;
+
+                let chksum = if checksum {
+                    let mut hasher = Sha256::new();
+                    io::copy(&mut entry, &mut hasher)?;
+
+                    Some(hasher.finalize().to_vec())
+                } else {
+                    None
+                };
+
+                (Some(size), chksum)
+            } else {
+                (None, None)
+            };
+
+        if debug > 2 {
+            eprintln!("Tar entry {}", path.display());
+        }
+
+        let mut file = PackageFile::new(path, Some(package_elem), size, Some(mode), chksum, time);
+
+        file.set_owner(owner);
+        file.set_link(link);
+
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+/// Joins a tar entry path on to the install prefix, rejecting entries which escape the prefix
+fn install_path(prefix: &Path, entry_path: &Path) -> Option<PathBuf> {
+    let mut path = prefix.to_path_buf();
+    let mut added = false;
+
+    for component in entry_path.components() {
+        match component {
+            Component::Normal(part) => {
+                path.push(part);
+                added = true;
+            }
+            Component::CurDir | Component::RootDir => (),
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    if added {
+        Some(path)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use plist::Value;
+
+use crate::packageman::{decode_hex, PackageFile};
+
+/// Reads a package's .<name>-files.plist. The dictionary has arrays of entries for
+/// files, conf_files, links and dirs, each entry having at least a file key
+pub fn read_files_plist(
+    files_plist: &Path,
+    package_elem: usize,
+) -> Result<Vec<PackageFile>, Box<dyn Error>> {
+    let plist = Value::from_file(files_plist)?;
+
+    let dict = plist
+        .as_dictionary()
+        .ok_or("File list is not a dictionary")?;
+
+    let mut files = Vec::new();
+
+    for (key, file_type) in [
+        ("files", None),
+        ("conf_files", None),
+        ("links", Some(libc::S_IFLNK | 0o777)),
+        ("dirs", None),
+    ] {
+        let Some(entries) = dict.get(key).and_then(Value::as_array) else {
+            continue;
+        };
+
+        for entry in entries.iter().filter_map(Value::as_dictionary) {
+            let Some(path) = entry.get("file").and_then(Value::as_string) else {
+                continue;
+            };
+
+            let chksum = entry
+                .get("sha256")
+                .and_then(Value::as_string)
+                .filter(|sha256| sha256.len() == 64 && sha256.is_ascii())
+                .and_then(|sha256| decode_hex(sha256).ok());
+
+            let size = entry
+                .get("size")
+                .and_then(Value::as_unsigned_integer)
+                .map(|size| size as usize);
+
+            let time = entry.get("mtime").and_then(Value::as_signed_integer);
+
+            files.push(PackageFile::new(
+                PathBuf::from(path),
+                Some(package_elem),
+                size,
+                file_type,
+                chksum,
+                time,
+            ));
+        }
+    }
+
+    Ok(files)
+}
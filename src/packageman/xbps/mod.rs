@@ -0,0 +1,106 @@
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use filesplist::read_files_plist;
+use pkgdb::read_pkgdb;
+
+use super::{LoadResult, Origin, Package, PackageMgr};
+
+mod filesplist;
+mod pkgdb;
+
+/// xbps metadata directory
+const META_DIR: &str = "/var/db/xbps";
+
+/// xbps package cache
+const CACHE_DIR: &str = "/var/cache/xbps";
+
+pub fn load_xbps(debug: u8) -> Result<LoadResult, Box<dyn Error>> {
+    let pkgdb_file = pkgdb_file().ok_or("No xbps package database found")?;
+
+    if debug > 0 {
+        eprintln!("Reading xbps package database {}", pkgdb_file.display());
+    }
+
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+
+    for pkg in read_pkgdb(&pkgdb_file)? {
+        let package_elem = packages.len();
+
+        // Read the package's file list
+        let files_plist = Path::new(META_DIR).join(format!(".{}-files.plist", pkg.name));
+
+        match read_files_plist(&files_plist, package_elem) {
+            Ok(package_files) => {
+                if debug > 1 {
+                    eprintln!("{} files found in {}", package_files.len(), pkg.name);
+                }
+
+                files.extend(package_files);
+            }
+            Err(e) => eprintln!(
+                "ERROR: Failed to read xbps file list {} ({e})",
+                files_plist.display()
+            ),
+        }
+
+        packages.push(Package::new(
+            OsString::from(format!("{}-{}", pkg.name, pkg.version)),
+            OsString::from(pkg.name),
+            OsString::from(pkg.version),
+            pkg.arch.map(OsString::from),
+            Origin::Mgr(PackageMgr::Xbps),
+        ));
+    }
+
+    if debug > 0 {
+        eprintln!("{} packages found", packages.len());
+    }
+
+    // Default ignores for xbps systems
+    let ignores = vec![
+        "^/var/db/xbps($|/.*)".into(),
+        "^/var/cache/xbps($|/.*)".into(),
+    ];
+
+    Ok((packages, files, ignores))
+}
+
+pub fn xbps_available() -> bool {
+    pkgdb_file().is_some()
+}
+
+/// Finds the package database (pkgdb-<version>.plist), using the latest format if several exist
+fn pkgdb_file() -> Option<PathBuf> {
+    let mut pkgdbs = fs::read_dir(META_DIR)
+        .ok()?
+        .filter_map(|ent| ent.ok())
+        .map(|ent| ent.path())
+        .filter(|path| {
+            path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with("pkgdb-") && name.ends_with(".plist")
+            })
+        })
+        .collect::<Vec<_>>();
+
+    pkgdbs.sort();
+
+    pkgdbs.pop()
+}
+
+/// Finds the .xbps for an installed package in the xbps package cache
+pub fn cached_xbps(package: &Package) -> Option<PathBuf> {
+    let xbps = Path::new(CACHE_DIR).join(format!(
+        "{}.{}.xbps",
+        package.fullnamestr(),
+        package.archstr()
+    ));
+
+    xbps.is_file().then_some(xbps)
+}
@@ -0,0 +1,55 @@
+use std::{error::Error, path::Path};
+
+use plist::Value;
+
+/// An installed package from the xbps package database
+pub struct PkgDbEntry {
+    pub name: String,
+    pub version: String,
+    pub arch: Option<String>,
+}
+
+/// Reads the package database, a dictionary of package name to package details
+pub fn read_pkgdb(pkgdb_file: &Path) -> Result<Vec<PkgDbEntry>, Box<dyn Error>> {
+    let pkgdb = Value::from_file(pkgdb_file)?;
+
+    let dict = pkgdb
+        .as_dictionary()
+        .ok_or("Package database is not a dictionary")?;
+
+    let mut entries = dict
+        .iter()
+        .filter_map(|(name, details)| {
+            let details = details.as_dictionary()?;
+
+            // Only installed packages
+            if details.get("state").and_then(Value::as_string) != Some("installed") {
+                return None;
+            }
+
+            // pkgver is <name>-<version>_<revision>
+            let version = details
+                .get("pkgver")
+                .and_then(Value::as_string)
+                .and_then(|pkgver| pkgver.strip_prefix(name.as_str()))
+                .and_then(|version| version.strip_prefix('-'))
+                .unwrap_or_default()
+                .to_string();
+
+            let arch = details
+                .get("architecture")
+                .and_then(Value::as_string)
+                .map(str::to_string);
+
+            Some(PkgDbEntry {
+                name: name.clone(),
+                version,
+                arch,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(entries)
+}
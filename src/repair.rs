@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    check::Report,
+    packageman::{Origin, Package, PackageDb, PackageMgr, Source},
+};
+
+/// Prints the commands to reinstall packages with missing or changed files, excluding
+/// configuration files which are expected to be modified
+pub fn repair_plan(packagedb: &PackageDb, report: &Report) {
+    // Find damaged packages
+    let mut damaged = report
+        .unfiltered()
+        .filter_map(|rep| rep.package_file())
+        .filter(|(_, config)| !config)
+        .map(|(package, _)| package)
+        .collect::<Vec<_>>();
+
+    damaged.sort();
+    damaged.dedup();
+
+    if damaged.is_empty() {
+        println!("# No packages need reinstalling");
+        return;
+    }
+
+    // Group packages by where they came from
+    let mut groups: BTreeMap<String, (Origin, Vec<&Package>)> = BTreeMap::new();
+
+    for package in damaged {
+        let package = packagedb.package(package);
+
+        groups
+            .entry(package.origin().to_string())
+            .or_insert_with(|| (package.origin(), Vec::new()))
+            .1
+            .push(package);
+    }
+
+    for (origin, packages) in groups.values_mut() {
+        packages.sort_by_key(|package| package.name_arch());
+
+        for command in reinstall_commands(*origin, packages) {
+            println!("{command}");
+        }
+    }
+}
+
+/// Builds the commands to reinstall packages from a single origin
+fn reinstall_commands(origin: Origin, packages: &[&Package]) -> Vec<String> {
+    let names = |format: &dyn Fn(&Package) -> String| {
+        packages
+            .iter()
+            .map(|package| shell_quote(&format(package)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let each = |format: &dyn Fn(&Package) -> String| {
+        packages
+            .iter()
+            .map(|package| format(package))
+            .collect::<Vec<_>>()
+    };
+
+    match origin {
+        Origin::Mgr(PackageMgr::Rpm) => {
+            let dnf = if Path::new("/usr/bin/dnf").exists() {
+                "dnf"
+            } else {
+                "yum"
+            };
+
+            vec![format!(
+                "{dnf} reinstall {}",
+                names(&|package| match package.archstr().as_ref() {
+                    "" => package.fullnamestr().into_owned(),
+                    arch => format!("{}.{arch}", package.fullnamestr()),
+                })
+            )]
+        }
+        Origin::Mgr(PackageMgr::Apt) => vec![format!(
+            "apt-get install --reinstall {}",
+            names(&|package| format!("{}:{}", package.namestr(), package.archstr()))
+        )],
+        Origin::Mgr(PackageMgr::Opkg) => vec![format!(
+            "opkg install --force-reinstall {}",
+            names(&|package| package.namestr().into_owned())
+        )],
+        Origin::Mgr(PackageMgr::Xbps) => vec![format!(
+            "xbps-install -f {}",
+            names(&|package| package.namestr().into_owned())
+        )],
+        Origin::Mgr(PackageMgr::Slackware) => vec![format!(
+            "slackpkg reinstall {}",
+            names(&|package| package.namestr().into_owned())
+        )],
+        Origin::Source(Source::Pip) => vec![format!(
+            "pip install --force-reinstall --no-deps {}",
+            names(&|package| format!("{}=={}", package.namestr(), package.versionstr()))
+        )],
+        Origin::Source(Source::Npm) => vec![format!(
+            "npm install -g {}",
+            names(&|package| format!("{}@{}", package.namestr(), package.versionstr()))
+        )],
+        Origin::Source(Source::Cpan) => vec![format!(
+            "cpanm --reinstall {}",
+            names(&|package| package.namestr().into_owned())
+        )],
+        Origin::Source(Source::Gem) => each(&|package| {
+            format!(
+                "gem pristine {} --version {}",
+                shell_quote(&package.namestr()),
+                shell_quote(&package.versionstr())
+            )
+        }),
+        Origin::Source(Source::Cargo) => each(&|package| {
+            format!(
+                "cargo install --force {} --version {}",
+                shell_quote(&package.namestr()),
+                shell_quote(&package.versionstr())
+            )
+        }),
+        Origin::Source(Source::Flatpak) => vec![
+            format!("# flatpak {}", names(&|package| package.name_arch())),
+            String::from("flatpak repair"),
+        ],
+        Origin::Source(Source::Nix) => vec![format!(
+            "nix-store --repair-path {}",
+            names(&|package| format!("/nix/store/{}", package.fullnamestr()))
+        )],
+        Origin::Source(Source::Snap) => vec![format!(
+            "# snaps must be reinstalled manually: {}",
+            names(&|package| package.namestr().into_owned())
+        )],
+        Origin::External => vec![format!(
+            "# external packages must be reinstalled manually: {}",
+            names(&|package| package.name_ver_arch())
+        )],
+        Origin::Tarball => vec![format!(
+            "# tarballs must be extracted again: {}",
+            names(&|package| package.name_ver_arch())
+        )],
+    }
+}
+
+/// Quotes a string for the shell if it contains anything other than safe characters
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:+=@/,%~".contains(c))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
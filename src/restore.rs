@@ -0,0 +1,143 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::{symlink, OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    archive::{extract_member, Member},
+    packageman::{normalise_path, PackageDb},
+};
+
+/// Extracts the packaged copy of each path from the package manager's archive cache, writing it
+/// beside the file with a .orig suffix, or under a restore directory
+pub fn restore(
+    packagedb: &PackageDb,
+    paths: &[PathBuf],
+    to: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let to = match to {
+        Some(dir) => Some(env::current_dir()?.join(dir)),
+        None => None,
+    };
+
+    for_each_path(paths, "restore", |path| {
+        let dest = match &to {
+            Some(dir) => dir.join(path.strip_prefix("/").unwrap_or(path)),
+            None => {
+                let mut orig = path.as_os_str().to_os_string();
+                orig.push(".orig");
+                PathBuf::from(orig)
+            }
+        };
+
+        restore_file(packagedb, path, &dest)?;
+
+        println!("{} restored to {}", path.display(), dest.display());
+
+        Ok(())
+    })
+}
+
+/// Calls f with each path made absolute and lexically normalised, printing any errors. Fails if
+/// f failed for any path
+pub fn for_each_path(
+    paths: &[PathBuf],
+    verb: &str,
+    mut f: impl FnMut(&Path) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let cwd = env::current_dir()?;
+
+    let mut failed = 0;
+
+    for path in paths {
+        let path = normalise_path(&cwd.join(path));
+
+        if let Err(e) = f(&path) {
+            eprintln!("ERROR: Failed to {verb} {} ({e})", path.display());
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        Err(format!(
+            "Failed to {verb} {failed} of {} paths",
+            paths.len()
+        ))?
+    }
+
+    Ok(())
+}
+
+/// Extracts the packaged copy of a file from a cached archive
+pub fn cached_member(packagedb: &PackageDb, path: &Path) -> Result<Member, Box<dyn Error>> {
+    let files = packagedb.find_files(path);
+
+    if files.is_empty() {
+        Err("not owned by a package")?
+    }
+
+    // Try each owning package, recording why each failed
+    let mut failures = Vec::new();
+
+    for file in files {
+        let Some(package) = *file.package() else {
+            continue;
+        };
+
+        let package = packagedb.package(package);
+
+        let Some(archive) = packagedb.cached_archive(package) else {
+            failures.push(format!("no cached archive for {}", package.name_ver_arch()));
+            continue;
+        };
+
+        // Files moved by diversions are stored at their original path
+        match extract_member(&archive, file.original_path()) {
+            Ok(Some(member)) => return Ok(member),
+            Ok(None) => failures.push(format!("not found in {}", archive.display())),
+            Err(e) => failures.push(format!("failed to read {} ({e})", archive.display())),
+        }
+    }
+
+    Err(failures.join(", "))?
+}
+
+/// Writes the packaged copy of a file to dest
+fn restore_file(packagedb: &PackageDb, path: &Path, dest: &Path) -> Result<(), Box<dyn Error>> {
+    let member = cached_member(packagedb, path)?;
+
+    if dest.symlink_metadata().is_ok() {
+        Err(format!("{} already exists", dest.display()))?
+    }
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match member.mode & libc::S_IFMT {
+        libc::S_IFREG => {
+            // Drop setuid, setgid and sticky bits so a restored copy can't grant privileges
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(member.mode & 0o777)
+                .open(dest)?;
+
+            file.write_all(&member.data)?;
+        }
+        libc::S_IFLNK => match &member.link {
+            Some(link) => symlink(link, dest)?,
+            None => Err("symbolic link has no target")?,
+        },
+        _ => Err(format!(
+            "can't restore {}",
+            unix_mode::to_string(member.mode)
+        ))?,
+    }
+
+    Ok(())
+}